@@ -1,161 +1,571 @@
 use crate::{
-    error::Result, find_pipeline_files, parse_task_definition, Config, Credentials, GitManager,
-    SupportedTask, TaskIssues,
+    console,
+    error::Result,
+    find_pipeline_files,
+    github_actions::GitHubWorkflow,
+    gitlab_ci::GitLabPipeline,
+    glob::glob_match_case_sensitive,
+    pipeline_detector::{detect_platform, Platform},
+    template_resolver::{ExternalTemplateReference, ResolvedTask},
+    Config, Credentials, GitManager, RepoEntry, RepositoryUrl, TaskImplementation, TaskIssues,
+    TemplateResolver,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 
-pub fn analyze_pipelines(
-    repos: &[String],
-    credentials: &Credentials,
-    config: &Config,
+/// One task occurrence in a shared template.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SharedTemplateFinding {
+    template_repo: String,
+    file_path: PathBuf,
+    /// Include chain inside the template repository.
+    include_chain: Vec<PathBuf>,
+    line: usize,
+    column: usize,
+    task_name: String,
+    version: String,
+    source: String,
+}
+
+/// Templates that pipelines pull in from other repositories through
+/// `resources.repositories`. Each repository and ref is fetched once, and every
+/// task found in it is reported once against the template repository together
+/// with the repositories that consume it.
+struct SharedTemplates<'a> {
+    credentials: Option<&'a Credentials>,
+    github_url: Option<&'a str>,
     verbose: bool,
-) -> Result<TaskIssues> {
-    println!("🔍 Analyzing {} repositories...", repos.len());
+    resolvers: HashMap<(String, Option<String>), Option<TemplateResolver>>,
+    errors: Vec<(String, String)>,
+    /// Files of template repositories that could not be parsed, per identity.
+    file_errors: Vec<(String, PathBuf, String)>,
+    /// Branch and commit checked out per template repository identity.
+    revisions: Vec<(String, String, Option<String>)>,
+    findings: BTreeMap<SharedTemplateFinding, BTreeSet<String>>,
+}
 
-    let mut issues = TaskIssues::default();
-    let all_tasks: HashSet<_> = config.get_all_tasks().into_iter().collect();
+impl<'a> SharedTemplates<'a> {
+    fn new(credentials: Option<&'a Credentials>, options: &'a AnalysisOptions) -> Self {
+        Self {
+            credentials,
+            github_url: options.github_url.as_deref(),
+            verbose: options.verbose,
+            resolvers: HashMap::new(),
+            errors: Vec::new(),
+            file_errors: Vec::new(),
+            revisions: Vec::new(),
+            findings: BTreeMap::new(),
+        }
+    }
 
-    for repo_url in repos {
-        if verbose {
-            println!("\n📂 Analyzing {}", repo_url);
+    /// Analyzes `external` on behalf of `consumer_url` and returns the names of
+    /// the tasks it contains.
+    fn collect(
+        &mut self,
+        consumer_url: &str,
+        external: &ExternalTemplateReference,
+    ) -> HashSet<String> {
+        let mut found_tasks = HashSet::new();
+
+        let Some(template_url) = external.repository.repository_url(consumer_url) else {
+            console!(
+                "   └─ ⚠️ Cannot resolve repository resource '{}' ({})",
+                external.repository.alias,
+                external.repository.kind
+            );
+            return found_tasks;
+        };
+        let reference = external.repository.branch_or_ref().map(String::from);
+
+        let key = (template_url.clone(), reference.clone());
+        if !self.resolvers.contains_key(&key) {
+            let resolver = self.load(&template_url, reference.as_deref());
+            self.resolvers.insert(key.clone(), resolver);
         }
+        let Some(resolver) = self.resolvers.get(&key).and_then(Option::as_ref) else {
+            return found_tasks;
+        };
 
-        let git_manager = GitManager::new(credentials.clone(), repo_url, verbose)?;
+        let template_repo = match &reference {
+            Some(reference) => format!("{}#{}", template_url, reference),
+            None => template_url,
+        };
 
-        match analyze_single_repo(
-            repo_url,
-            &git_manager,
-            config,
-            &all_tasks,
-            &mut issues,
-            verbose,
-        ) {
-            Ok(repo_tasks) => {
-                for task in &all_tasks {
-                    let task_name = task.to_string();
-                    if !repo_tasks.contains(&task_name) {
-                        issues.add_missing_task(repo_url, &task_name);
+        if self.verbose {
+            console!(
+                "   └─ 📎 Following template {} in {}",
+                external.path.display(),
+                template_repo
+            );
+        }
+
+        for resolved in resolver.resolve_from(&external.path) {
+            found_tasks.insert(resolved.task.name.to_lowercase());
+            self.findings
+                .entry(SharedTemplateFinding {
+                    template_repo: template_repo.clone(),
+                    file_path: resolved.file_path,
+                    include_chain: resolved.include_chain,
+                    line: resolved.task.line,
+                    column: resolved.task.column,
+                    task_name: resolved.task.name,
+                    version: resolved.task.version,
+                    source: resolved.task.source,
+                })
+                .or_default()
+                .insert(consumer_url.to_string());
+        }
+
+        found_tasks
+    }
+
+    fn load(&mut self, template_url: &str, reference: Option<&str>) -> Option<TemplateResolver> {
+        let result = Credentials::for_remote(self.credentials, template_url, self.github_url)
+            .and_then(|credentials| GitManager::new(credentials, template_url, self.verbose))
+            .map(|git_manager| git_manager.with_reference(reference))
+            .and_then(|git_manager| {
+                // A worker may be analyzing the same repository
+                git_manager.with_lock(|git_manager| {
+                    let branch = git_manager.clone_or_update()?;
+                    let identity = match reference {
+                        Some(reference) => format!("{}#{}", template_url, reference),
+                        None => template_url.to_string(),
+                    };
+                    let commit = GitManager::head_commit(git_manager.get_repo_path());
+                    self.revisions.push((identity.clone(), branch, commit));
+                    let files = find_pipeline_files(git_manager.get_repo_path(), self.verbose)?;
+                    let resolver =
+                        TemplateResolver::load(git_manager.get_repo_path(), &files, self.verbose)?;
+                    for (file, error) in resolver.parse_failures() {
+                        self.file_errors
+                            .push((identity.clone(), file.clone(), error.clone()));
                     }
-                }
+                    Ok(resolver)
+                })
+            });
+
+        match result {
+            Ok(resolver) => Some(resolver),
+            Err(e) => {
+                console!("Error fetching template repository {}: {}", template_url, e);
+                self.errors.push((template_url.to_string(), e.to_string()));
+                None
             }
+        }
+    }
+
+    fn add_to(self, issues: &mut TaskIssues, config: &Config, verbose: bool) {
+        for (template_url, error) in &self.errors {
+            issues.add_failed_repo(template_url, error);
+        }
+        for (identity, file, error) in &self.file_errors {
+            issues.add_failed_file(identity, file, error);
+        }
+        for (identity, branch, commit) in self.revisions {
+            if let Some(commit) = commit {
+                issues.repo_commits.insert(identity.clone(), commit);
+            }
+            issues.repo_branches.insert(identity, branch);
+        }
+        for (finding, consumers) in self.findings {
+            issues.add_implementation(
+                &finding.task_name,
+                TaskImplementation {
+                    repo_name: finding.template_repo,
+                    version: finding.version,
+                    file_path: finding.file_path,
+                    line: finding.line,
+                    column: finding.column,
+                    source: finding.source,
+                    include_chain: finding.include_chain,
+                    consumers: consumers.into_iter().collect(),
+                },
+                config,
+                verbose,
+            );
+        }
+    }
+}
+
+/// How `analyze_pipelines` fetches and analyzes repositories.
+#[derive(Debug, Clone)]
+pub struct AnalysisOptions {
+    pub verbose: bool,
+    /// Number of repositories fetched and analyzed at the same time.
+    pub jobs: usize,
+    /// Branches tried when a remote does not report its HEAD; empty for
+    /// `DEFAULT_FALLBACK_BRANCHES`.
+    pub fallback_branches: Vec<String>,
+    /// The `--github-url` API of a GitHub Enterprise Server, whose remotes
+    /// may be fetched with `GITHUB_TOKEN`.
+    pub github_url: Option<String>,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            verbose: false,
+            jobs: 1,
+            fallback_branches: Vec::new(),
+            github_url: None,
+        }
+    }
+}
+
+/// Replaces each remote repository without a ref by one entry per branch
+/// matching `patterns`, e.g. `main` and `release/*`. Local checkouts and
+/// repositories with a ref are kept as they are.
+pub fn expand_branches(
+    repos: &[RepoEntry],
+    patterns: &[String],
+    credentials: Option<&Credentials>,
+    options: &AnalysisOptions,
+) -> Result<Vec<RepoEntry>> {
+    let verbose = options.verbose;
+    let mut expanded = Vec::new();
+    for entry in repos {
+        if patterns.is_empty() || entry.path.is_some() || entry.reference.is_some() {
+            expanded.push(entry.clone());
+            continue;
+        }
+
+        let git_manager = GitManager::new(
+            Credentials::for_remote(credentials, &entry.url, options.github_url.as_deref())?,
+            &entry.url,
+            verbose,
+        )?;
+        let branches = match git_manager.remote_branches() {
+            Ok(branches) => branches,
             Err(e) => {
-                println!("Error analyzing repository {}: {}", repo_url, e);
+                // Analyzing the default branch reports the error
+                console!("⚠️ Cannot list branches of {}: {}", entry.url, e);
+                expanded.push(entry.clone());
                 continue;
             }
+        };
+
+        let mut matching: Vec<_> = branches
+            .into_iter()
+            // Branch names are case-sensitive in git
+            .filter(|branch| {
+                patterns
+                    .iter()
+                    .any(|p| glob_match_case_sensitive(p, branch))
+            })
+            .collect();
+        matching.sort();
+        if matching.is_empty() {
+            console!(
+                "⚠️ No branch of {} matches {}",
+                entry.url,
+                patterns.join(", ")
+            );
+        } else if verbose {
+            console!(
+                "🌿 {}: {}",
+                RepositoryUrl::parse(&entry.url).display_name(),
+                matching.join(", ")
+            );
+        }
+        expanded.extend(matching.into_iter().map(|branch| RepoEntry {
+            reference: Some(branch),
+            ..entry.clone()
+        }));
+    }
+    Ok(expanded)
+}
+
+/// The result of analyzing one repository on its own, before the templates it
+/// pulls in from other repositories are followed.
+struct RepoAnalysis {
+    repo_url: String,
+    issues: TaskIssues,
+    /// Tasks found in the repository and its references to shared templates.
+    result: Result<(HashSet<String>, Vec<ExternalTemplateReference>)>,
+}
+
+/// Analyzes every repository. Remote repositories are cloned, which needs
+/// `credentials`; local checkouts are analyzed in place. Up to `options.jobs`
+/// repositories are fetched and analyzed at the same time; the output of each
+/// is printed as one block, in the order of `repos`.
+pub fn analyze_pipelines(
+    repos: &[RepoEntry],
+    credentials: Option<&Credentials>,
+    config: &Config,
+    options: &AnalysisOptions,
+) -> Result<TaskIssues> {
+    let verbose = options.verbose;
+    console!("🔍 Analyzing {} repositories...", repos.len());
+
+    let mut issues = TaskIssues::default();
+    let required_tasks: HashSet<_> = config.get_required_tasks().into_iter().collect();
+    let mut shared_templates = SharedTemplates::new(credentials, options);
+
+    let mut add = |analysis: RepoAnalysis| {
+        issues.merge(analysis.issues);
+        match analysis.result {
+            Ok((mut found_tasks, external_templates)) => {
+                for external in &external_templates {
+                    found_tasks.extend(shared_templates.collect(&analysis.repo_url, external));
+                }
+                for task in &required_tasks {
+                    let task_name = task.to_string();
+                    if !found_tasks.contains(&task_name) {
+                        issues.add_missing_task(&analysis.repo_url, &task_name);
+                    }
+                }
+            }
+            Err(e) => issues.add_failed_repo(&analysis.repo_url, &e.to_string()),
+        }
+    };
+
+    let jobs = options.jobs.clamp(1, repos.len().max(1));
+    if jobs == 1 {
+        for entry in repos {
+            add(analyze_repo(entry, credentials, config, options));
         }
+    } else {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let sender = sender.clone();
+                let next = &next;
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(entry) = repos.get(index) else {
+                        break;
+                    };
+                    let (analysis, output) =
+                        console::capture(|| analyze_repo(entry, credentials, config, options));
+                    if sender.send((index, analysis, output)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            // Results arrive in completion order; keep the order of `repos`
+            let mut pending = BTreeMap::new();
+            let mut index = 0;
+            for (position, analysis, output) in receiver {
+                pending.insert(position, (analysis, output));
+                while let Some((analysis, output)) = pending.remove(&index) {
+                    console::flush(&output);
+                    add(analysis);
+                    index += 1;
+                }
+            }
+        });
     }
 
-    println!("\n✅ Analysis complete");
+    shared_templates.add_to(&mut issues, config, verbose);
+
+    console!("\n✅ Analysis complete");
     Ok(issues)
 }
 
+fn analyze_repo(
+    entry: &RepoEntry,
+    credentials: Option<&Credentials>,
+    config: &Config,
+    options: &AnalysisOptions,
+) -> RepoAnalysis {
+    let verbose = options.verbose;
+    let repo_url = entry.identity();
+    if verbose {
+        console!("\n📂 Analyzing {}", repo_url);
+    }
+
+    let mut issues = TaskIssues::default();
+    let result = with_checkout(entry, credentials, options, |repo_path, branch| {
+        if let Some(branch) = branch {
+            issues.repo_branches.insert(repo_url.clone(), branch);
+        }
+        if let Some(commit) = GitManager::head_commit(repo_path) {
+            issues.repo_commits.insert(repo_url.clone(), commit);
+        }
+        analyze_single_repo(&repo_url, repo_path, config, &mut issues, verbose)
+    });
+    if let Err(e) = &result {
+        console!("Error analyzing repository {}: {}", repo_url, e);
+    }
+
+    RepoAnalysis {
+        repo_url,
+        issues,
+        result,
+    }
+}
+
+/// Runs `f` on the directory to analyze and the branch checked out: a local
+/// checkout as is, or a fresh clone of a remote repository. The clone stays
+/// locked until `f` returns.
+fn with_checkout<T>(
+    entry: &RepoEntry,
+    credentials: Option<&Credentials>,
+    options: &AnalysisOptions,
+    f: impl FnOnce(&Path, Option<String>) -> Result<T>,
+) -> Result<T> {
+    if let Some(path) = &entry.path {
+        return f(path, None);
+    }
+
+    let git_manager = GitManager::new(
+        Credentials::for_remote(credentials, &entry.url, options.github_url.as_deref())?,
+        &entry.url,
+        options.verbose,
+    )?
+    .with_reference(entry.reference.as_deref())
+    .with_fallback_branches(&options.fallback_branches);
+    git_manager.with_lock(|git_manager| {
+        let branch = git_manager.clone_or_update()?;
+        f(git_manager.get_repo_path(), Some(branch))
+    })
+}
+
 fn analyze_single_repo(
     repo_url: &str,
-    git_manager: &GitManager,
+    repo_path: &Path,
     config: &Config,
-    _all_tasks: &HashSet<SupportedTask>,
     issues: &mut TaskIssues,
     verbose: bool,
-) -> Result<HashSet<String>> {
-    let short_name = repo_url
-        .split('/')
-        .last()
-        .unwrap_or(repo_url)
-        .trim_end_matches("/_git/")
-        .trim_end_matches(".git");
+) -> Result<(HashSet<String>, Vec<ExternalTemplateReference>)> {
+    let short_name = RepositoryUrl::parse(repo_url).display_name();
 
     if !verbose {
-        println!("\n📂 Analyzing {}", short_name);
+        console!("\n📂 Analyzing {}", short_name);
     }
 
-    git_manager.clone_or_update()?;
-    let pipeline_files = find_pipeline_files(git_manager.get_repo_path(), verbose)?;
+    let pipeline_files = find_pipeline_files(repo_path, verbose)?;
 
     // Add to analyzed repos regardless of whether we find pipeline files
     issues.repos_analyzed.insert(repo_url.to_string());
 
     if pipeline_files.is_empty() {
         if !verbose {
-            println!("   └─ Found 0 pipeline files, skipping");
+            console!("   └─ Found 0 pipeline files, skipping");
         }
         issues.repos_skipped.insert(repo_url.to_string());
-        return Ok(HashSet::new());
+        return Ok((HashSet::new(), Vec::new()));
     }
 
     if !verbose {
-        println!("   └─ Found {} pipeline files", pipeline_files.len());
+        console!("   └─ Found {} pipeline files", pipeline_files.len());
     }
 
+    let (azure_files, other_files): (Vec<PathBuf>, Vec<PathBuf>) =
+        pipeline_files.into_iter().partition(|file| {
+            let relative = file.strip_prefix(repo_path).unwrap_or(file);
+            detect_platform(relative) == Platform::AzurePipelines
+        });
+
+    let resolver = TemplateResolver::load(repo_path, &azure_files, verbose)?;
+    for (file, error) in resolver.parse_failures() {
+        issues.add_failed_file(repo_url, file, error);
+    }
+    let resolution = resolver.resolve();
+    let mut resolved_tasks = resolution.tasks;
+    resolved_tasks.extend(resolve_standalone_files(
+        repo_url,
+        repo_path,
+        &other_files,
+        issues,
+        verbose,
+    )?);
+
     let mut found_tasks = HashSet::new();
-    for file in &pipeline_files {
-        let tasks = analyze_pipeline_file(file, verbose)?;
-        for task_with_version in &tasks {
-            if let Some((task_name, version)) = task_with_version.split_once('@') {
-                if verbose {
-                    println!(
-                        "   └─ 📝 Processing implementation: {} @ {}",
-                        task_name, version
-                    );
-                }
-                issues.add_implementation(
-                    task_name,
-                    repo_url,
-                    version.to_string(),
-                    file.clone(),
-                    config,
-                    verbose,
-                );
-                found_tasks.insert(task_name.to_string());
-            }
+    let mut seen = HashSet::new();
+    for resolved in resolved_tasks {
+        let task = &resolved.task;
+        // A template included from several roots is reported once, through
+        // the first chain that reaches it
+        if !seen.insert((
+            resolved.file_path.clone(),
+            task.line,
+            task.name.to_lowercase(),
+        )) {
+            continue;
         }
+
+        let implementation = TaskImplementation {
+            repo_name: repo_url.to_string(),
+            version: task.version.clone(),
+            file_path: resolved.file_path,
+            line: task.line,
+            column: task.column,
+            source: task.source.clone(),
+            include_chain: resolved.include_chain,
+            consumers: Vec::new(),
+        };
+
+        if verbose {
+            console!(
+                "   └─ 📝 Processing implementation: {} @ {} (via {})",
+                task.name,
+                task.version,
+                implementation.include_path()
+            );
+        }
+
+        issues.add_implementation(&task.name, implementation, config, verbose);
+        found_tasks.insert(task.name.to_lowercase());
     }
 
     if !verbose {
-        println!("   └─ Found {} unique tasks", found_tasks.len());
+        console!("   └─ Found {} unique tasks", found_tasks.len());
     }
 
-    Ok(found_tasks)
+    Ok((found_tasks, resolution.external_templates))
 }
 
-fn analyze_pipeline_file(file_path: &PathBuf, verbose: bool) -> Result<HashSet<String>> {
-    let content = fs::read_to_string(file_path)?;
+/// GitHub Actions and GitLab CI files are not followed through an include
+/// graph, so every reference is attributed to the file it is written in.
+fn resolve_standalone_files(
+    repo_url: &str,
+    repo_path: &Path,
+    files: &[PathBuf],
+    issues: &mut TaskIssues,
+    verbose: bool,
+) -> Result<Vec<ResolvedTask>> {
+    let mut resolved = Vec::new();
 
-    if !content.contains("task:") {
+    for file in files {
+        let relative = file.strip_prefix(repo_path).unwrap_or(file).to_path_buf();
         if verbose {
-            println!("   └─ Skipping: No task definitions found");
+            console!("📄 Analyzing workflow file: {}", relative.display());
         }
-        return Ok(HashSet::new());
-    }
 
-    let mut found_tasks = HashSet::new();
-
-    if verbose {
-        let relative_path = file_path
-            .strip_prefix(std::path::Path::new("C:\\repos\\ciprobe\\temp_repos"))
-            .unwrap_or(file_path);
-        println!("📄 Analyzing pipeline file: {}", relative_path.display());
-    }
-
-    for line in content.lines() {
-        if let Some(task) = parse_task_definition(line) {
-            let task_with_version = format!("{}@{}", task.name, task.version);
-
-            if verbose {
-                println!(
-                    "   ├─ 🔍 Found task: {} @ version {}",
-                    task.name, task.version
-                );
+        let content = fs::read_to_string(file)?;
+        let parsed = match detect_platform(&relative) {
+            Platform::GitHubActions => GitHubWorkflow::parse(&content)
+                .map(|workflow| workflow.tasks().cloned().collect::<Vec<_>>()),
+            Platform::GitLabCi => GitLabPipeline::parse(&content)
+                .map(|pipeline| pipeline.tasks().cloned().collect::<Vec<_>>()),
+            Platform::AzurePipelines => continue,
+        };
+        let tasks = match parsed {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                console!("   └─ ⚠️ Skipping {}: {}", relative.display(), e);
+                issues.add_failed_file(repo_url, &relative, &e.to_string());
+                continue;
             }
+        };
 
-            found_tasks.insert(task_with_version);
+        for task in tasks {
+            resolved.push(ResolvedTask {
+                task,
+                file_path: relative.clone(),
+                include_chain: vec![relative.clone()],
+            });
         }
     }
 
-    if verbose && found_tasks.is_empty() {
-        println!("   └─ No tasks found in this file");
-    }
-
-    Ok(found_tasks)
+    Ok(resolved)
 }
@@ -0,0 +1,311 @@
+use crate::{
+    error::Result,
+    line_parser::parse_task_reference,
+    yaml_document::{parse_documents, source_line, YamlNode},
+};
+
+const STEP_KEYS: [&str; 10] = [
+    "script",
+    "bash",
+    "pwsh",
+    "powershell",
+    "checkout",
+    "download",
+    "downloadBuild",
+    "getPackage",
+    "publish",
+    "reviewApp",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStep {
+    pub name: String,
+    pub version: String,
+    pub line: usize,
+    pub column: usize,
+    /// The trimmed source line the reference is written on.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateKind {
+    Stage,
+    Job,
+    Step,
+    Variable,
+    Extends,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateReference {
+    pub kind: TemplateKind,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// An entry of `resources.repositories`, which `template: file@alias` refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepositoryResource {
+    pub alias: String,
+    pub kind: String,
+    pub name: String,
+    pub reference: Option<String>,
+}
+
+impl RepositoryResource {
+    /// Builds the clone URL of the resource. Azure Repos names are resolved
+    /// against the organization (and project) of the consuming repository.
+    pub fn repository_url(&self, consumer_url: &str) -> Option<String> {
+        match self.kind.as_str() {
+            "git" => {
+                let consumer_project = &consumer_url[..consumer_url.find("/_git/")?];
+                match self.name.split_once('/') {
+                    Some((project, repo)) => {
+                        let organization = &consumer_project[..consumer_project.rfind('/')?];
+                        Some(format!("{}/{}/_git/{}", organization, project, repo))
+                    }
+                    None => Some(format!("{}/_git/{}", consumer_project, self.name)),
+                }
+            }
+            "github" => Some(format!("https://github.com/{}", self.name)),
+            "bitbucket" => Some(format!("https://bitbucket.org/{}", self.name)),
+            _ => None,
+        }
+    }
+
+    /// The ref to check out, without the `refs/heads/` prefix.
+    pub fn branch_or_ref(&self) -> Option<&str> {
+        self.reference
+            .as_deref()
+            .map(|r| r.strip_prefix("refs/heads/").unwrap_or(r))
+    }
+}
+
+/// A typed node found while walking `stages`/`jobs`/`steps`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineStep {
+    Task(TaskStep),
+    Template(TemplateReference),
+    Other {
+        kind: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Level {
+    Stages,
+    Jobs,
+    Steps,
+    Variables,
+}
+
+/// The structure of an Azure Pipelines YAML file (a pipeline or a template).
+#[derive(Debug, Default)]
+pub struct AzurePipeline {
+    pub steps: Vec<PipelineStep>,
+    pub repositories: Vec<RepositoryResource>,
+}
+
+impl AzurePipeline {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut pipeline = AzurePipeline::default();
+        for document in parse_documents(content)? {
+            pipeline.walk_container(&document);
+        }
+        for step in &mut pipeline.steps {
+            if let PipelineStep::Task(task) = step {
+                task.source = source_line(content, task.line);
+            }
+        }
+        Ok(pipeline)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &TaskStep> {
+        self.steps.iter().filter_map(|step| match step {
+            PipelineStep::Task(task) => Some(task),
+            _ => None,
+        })
+    }
+
+    pub fn repository(&self, alias: &str) -> Option<&RepositoryResource> {
+        self.repositories.iter().find(|r| r.alias == alias)
+    }
+
+    pub fn templates(&self) -> impl Iterator<Item = &TemplateReference> {
+        self.steps.iter().filter_map(|step| match step {
+            PipelineStep::Template(template) => Some(template),
+            _ => None,
+        })
+    }
+
+    /// Walks a mapping that may hold `stages`, `jobs` or `steps`: the pipeline
+    /// root, a template root, a stage or a job.
+    fn walk_container(&mut self, node: &YamlNode) {
+        let Some(entries) = node.as_mapping() else {
+            return;
+        };
+
+        for (key, value) in entries {
+            match key.as_str() {
+                "stages" => self.walk_list(value, Level::Stages),
+                "jobs" => self.walk_list(value, Level::Jobs),
+                "steps" => self.walk_list(value, Level::Steps),
+                "variables" => self.walk_list(value, Level::Variables),
+                "extends" => self.walk_template_call(value, TemplateKind::Extends),
+                "parameters" => self.walk_parameter_declarations(value),
+                "strategy" => self.walk_lifecycle_hooks(value),
+                "resources" => self.add_repositories(value),
+                _ if is_template_expression(key) => self.walk_container(value),
+                _ => {}
+            }
+        }
+    }
+
+    fn walk_list(&mut self, node: &YamlNode, level: Level) {
+        if let Some(items) = node.as_sequence() {
+            for item in items {
+                self.walk_item(item, level);
+            }
+        }
+    }
+
+    fn walk_item(&mut self, item: &YamlNode, level: Level) {
+        let Some(entries) = item.as_mapping() else {
+            return;
+        };
+
+        // `- ${{ if ... }}:` / `- ${{ each ... }}:` insert items at the same level.
+        for (key, value) in entries {
+            if is_template_expression(key) {
+                match value.as_sequence() {
+                    Some(_) => self.walk_list(value, level),
+                    None => self.walk_item(value, level),
+                }
+            }
+        }
+
+        if item.get("template").is_some() {
+            let kind = match level {
+                Level::Stages => TemplateKind::Stage,
+                Level::Jobs => TemplateKind::Job,
+                Level::Steps => TemplateKind::Step,
+                Level::Variables => TemplateKind::Variable,
+            };
+            self.walk_template_call(item, kind);
+        } else if let Some(task) = item.get("task") {
+            self.add_task(task);
+        } else if item.get("stage").is_some()
+            || item.get("job").is_some()
+            || item.get("deployment").is_some()
+        {
+            self.walk_container(item);
+        } else if let Some(key) = STEP_KEYS.iter().find(|k| item.get(k).is_some()) {
+            self.steps.push(PipelineStep::Other {
+                kind: key.to_string(),
+                line: item.line,
+                column: item.column,
+            });
+        }
+    }
+
+    fn add_repositories(&mut self, resources: &YamlNode) {
+        let Some(repositories) = resources.get("repositories").and_then(|r| r.as_sequence()) else {
+            return;
+        };
+
+        for repository in repositories {
+            let field = |key: &str| repository.get(key).and_then(|v| v.as_str());
+            let (Some(alias), Some(name)) = (field("repository"), field("name")) else {
+                continue;
+            };
+            self.repositories.push(RepositoryResource {
+                alias: alias.to_string(),
+                kind: field("type").unwrap_or("git").to_string(),
+                name: name.to_string(),
+                reference: field("ref").map(String::from),
+            });
+        }
+    }
+
+    fn add_task(&mut self, node: &YamlNode) {
+        let Some(task) = node.as_str().and_then(parse_task_reference) else {
+            return;
+        };
+        self.steps.push(PipelineStep::Task(TaskStep {
+            name: task.name,
+            version: task.version,
+            line: node.line,
+            column: node.column,
+            source: String::new(),
+        }));
+    }
+
+    /// Records a `template:` reference and walks the parameters passed to it,
+    /// which often carry step, job or stage lists of their own.
+    fn walk_template_call(&mut self, node: &YamlNode, kind: TemplateKind) {
+        let Some(template) = node.get("template") else {
+            return;
+        };
+        if let Some(path) = template.as_str() {
+            self.steps.push(PipelineStep::Template(TemplateReference {
+                kind,
+                path: path.to_string(),
+                line: template.line,
+                column: template.column,
+            }));
+        }
+        if let Some(parameters) = node.get("parameters") {
+            self.walk_parameter_value(parameters);
+        }
+    }
+
+    /// Walks `parameters:` at a template root: either a list of declarations
+    /// with `default` values or the legacy `name: default` mapping.
+    fn walk_parameter_declarations(&mut self, node: &YamlNode) {
+        match node.as_sequence() {
+            Some(items) => {
+                for default in items.iter().filter_map(|item| item.get("default")) {
+                    self.walk_parameter_value(default);
+                }
+            }
+            None => self.walk_parameter_value(node),
+        }
+    }
+
+    fn walk_parameter_value(&mut self, node: &YamlNode) {
+        if let Some(items) = node.as_sequence() {
+            let has_key = |key: &str| items.iter().any(|item| item.get(key).is_some());
+            let level = if has_key("stage") {
+                Level::Stages
+            } else if has_key("job") || has_key("deployment") {
+                Level::Jobs
+            } else {
+                Level::Steps
+            };
+            self.walk_list(node, level);
+        } else if let Some(entries) = node.as_mapping() {
+            for (_, value) in entries {
+                self.walk_parameter_value(value);
+            }
+        }
+    }
+
+    /// Walks deployment job strategies (`runOnce`, `rolling`, `canary`) whose
+    /// lifecycle hooks each carry their own `steps`.
+    fn walk_lifecycle_hooks(&mut self, node: &YamlNode) {
+        let Some(entries) = node.as_mapping() else {
+            return;
+        };
+        for (_, value) in entries {
+            self.walk_container(value);
+            self.walk_lifecycle_hooks(value);
+        }
+    }
+}
+
+fn is_template_expression(key: &str) -> bool {
+    key.trim_start().starts_with("${{")
+}
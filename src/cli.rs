@@ -1,18 +1,45 @@
+use crate::discovery::RepoFilter;
 use crate::error::{Error, Result};
+use crate::gate::{Gate, GateCheck};
+use crate::report::{find_renderer, ReportOutput, ReportRenderer, RENDERERS};
 use std::path::PathBuf;
 
 #[derive(Default, Debug)]
 pub struct Cli {
     pub repos: String,
+    pub repos_file: Option<PathBuf>,
+    pub paths: Vec<PathBuf>,
+    pub azure_org: Option<String>,
+    pub azure_projects: Vec<String>,
+    pub azure_url: Option<String>,
+    pub github_org: Option<String>,
+    pub github_url: Option<String>,
+    pub topics: Vec<String>,
+    pub filter: RepoFilter,
     pub credentials: Option<String>,
     pub config_path: Option<PathBuf>,
     pub verbose: bool,
+    pub jobs: usize,
+    pub fallback_branches: Vec<String>,
+    /// Branch patterns (`*` and `?`) analyzed in every remote repository.
+    pub branches: Vec<String>,
+    pub format: Option<&'static dyn ReportRenderer>,
+    pub output: Option<PathBuf>,
+    pub reports: Vec<ReportOutput>,
+    pub gate: Gate,
 }
 
 impl Cli {
     pub fn parse() -> Result<Self> {
-        let mut cli = Cli::default();
-        let mut args = std::env::args().skip(1);
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Cli {
+            jobs: 1,
+            ..Cli::default()
+        };
+        let mut args = args.into_iter();
 
         while let Some(arg) = args.next() {
             match arg.as_str() {
@@ -21,23 +48,160 @@ impl Cli {
                         .next()
                         .ok_or_else(|| Error::Cli("--repos requires a value".to_string()))?;
                 }
+                "--repos-file" => {
+                    cli.repos_file =
+                        Some(args.next().map(PathBuf::from).ok_or_else(|| {
+                            Error::Cli("--repos-file requires a path".to_string())
+                        })?);
+                }
+                "--path" => {
+                    cli.paths.push(
+                        args.next()
+                            .map(PathBuf::from)
+                            .ok_or_else(|| Error::Cli("--path requires a directory".to_string()))?,
+                    );
+                }
+                "--azure-org" | "--project" | "--azure-url" | "--github-org" | "--github-url"
+                | "--topic" | "--include" | "--exclude" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Error::Cli(format!("{} requires a value", arg)))?;
+                    match arg.as_str() {
+                        "--azure-org" => cli.azure_org = Some(value),
+                        "--project" => cli.azure_projects.push(value),
+                        "--azure-url" => cli.azure_url = Some(value),
+                        "--github-org" => cli.github_org = Some(value),
+                        "--github-url" => cli.github_url = Some(value),
+                        "--topic" => cli.topics.push(value),
+                        "--include" => cli.filter.include.push(value),
+                        _ => cli.filter.exclude.push(value),
+                    }
+                }
                 "--credentials" => {
                     cli.credentials = args.next();
                 }
                 "--config" => {
                     cli.config_path = args.next().map(PathBuf::from);
                 }
+                "--format" => {
+                    let format = args
+                        .next()
+                        .ok_or_else(|| Error::Cli("--format requires a value".to_string()))?;
+                    cli.format = Some(find_renderer(&format)?);
+                }
+                "--output" | "-o" => {
+                    cli.output = Some(
+                        args.next()
+                            .map(PathBuf::from)
+                            .ok_or_else(|| Error::Cli("--output requires a path".to_string()))?,
+                    );
+                }
+                "--report" => {
+                    let spec = args.next().ok_or_else(|| {
+                        Error::Cli("--report requires <format>=<path>".to_string())
+                    })?;
+                    cli.reports.push(ReportOutput::parse(&spec)?);
+                }
+                "--fail-on" => {
+                    let checks = args
+                        .next()
+                        .ok_or_else(|| Error::Cli("--fail-on requires a value".to_string()))?;
+                    for check in checks.split(',').filter(|c| !c.trim().is_empty()) {
+                        cli.gate.fail_on(check.parse()?);
+                    }
+                }
+                "--max-invalid" | "--max-missing" | "--max-unknown" | "--max-forbidden"
+                | "--max-errors" => {
+                    let check: GateCheck = arg.trim_start_matches("--max-").parse()?;
+                    let max = args
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .ok_or_else(|| Error::Cli(format!("{} requires a number", arg)))?;
+                    cli.gate.max(check, max);
+                }
+                "-j" | "--jobs" => {
+                    cli.jobs = args
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| {
+                            Error::Cli(format!("{} requires a number greater than 0", arg))
+                        })?;
+                }
+                "--fallback-branches" => {
+                    let branches = args.next().ok_or_else(|| {
+                        Error::Cli("--fallback-branches requires a value".to_string())
+                    })?;
+                    cli.fallback_branches = branches
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|b| !b.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                "--branches" => {
+                    let patterns = args
+                        .next()
+                        .ok_or_else(|| Error::Cli("--branches requires a value".to_string()))?;
+                    cli.branches = patterns
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
                 "-v" | "--verbose" => {
                     cli.verbose = true;
                 }
                 "-h" | "--help" => {
                     println!("Usage: ciprobe [OPTIONS]");
                     println!("\nOptions:");
-                    println!("  --repos <URLS>         Comma-separated list of repository URLs to analyze");
+                    println!("  --repos <URLS>         Comma-separated repository URLs, each optionally url#branch, #tag or #sha");
+                    println!("  --repos-file <PATH>    File with repository URLs: one per line, or a YAML/JSON inventory");
+                    println!("  --path <DIR>           Analyze a local checkout without cloning, can be repeated");
+                    println!("  --azure-org <ORG>      Analyze the repositories of an Azure DevOps organization");
+                    println!(
+                        "  --project <PROJECT>    Limit --azure-org to a project, can be repeated"
+                    );
+                    println!("  --azure-url <URL>      Azure DevOps base URL (default: https://dev.azure.com)");
+                    println!("  --github-org <ORG>     Analyze the repositories of a GitHub organization");
+                    println!(
+                        "  --github-url <URL>     GitHub API URL (default: https://api.github.com)"
+                    );
+                    println!("  --topic <TOPIC>        Only analyze GitHub repositories with the topic, can be repeated");
+                    println!("  --include <PATTERN>    Only analyze discovered repositories matching the name pattern, can be repeated");
+                    println!("  --exclude <PATTERN>    Skip discovered repositories matching the name pattern, can be repeated");
                     println!("  --credentials <CREDS>  Git credentials in username:token format");
                     println!("  --config <PATH>        Path to config file (default: ./ciprobeconfig.yml)");
+                    let formats: Vec<_> = RENDERERS.iter().map(|r| r.name()).collect();
+                    println!(
+                        "  --format <FORMAT>      Report format: {} (default: markdown)",
+                        formats.join(", ")
+                    );
+                    println!("  -o, --output <PATH>    Report file (default: report.<ext>)");
+                    println!(
+                        "  --report <FMT>=<PATH>  Additional report to write, can be repeated"
+                    );
+                    println!("  --fail-on <CHECKS>     Fail on any invalid, missing, unknown, forbidden or error finding");
+                    println!(
+                        "  --max-invalid <N>      Fail when more than N task versions are invalid"
+                    );
+                    println!(
+                        "  --max-missing <N>      Fail when more than N required tasks are missing"
+                    );
+                    println!("  --max-unknown <N>      Fail when more than N tasks are not in the config");
+                    println!("  --max-forbidden <N>    Fail when forbidden tasks are used more than N times");
+                    println!("  --max-errors <N>       Fail when more than N repositories or pipeline files cannot be analyzed");
+                    println!("  -j, --jobs <N>         Fetch and analyze N repositories in parallel (default: 1)");
+                    println!("  --branches <GLOBS>     Analyze every branch matching these comma-separated patterns, e.g. main,release/*");
+                    println!("  --fallback-branches <LIST>  Branches tried when a remote does not report its default branch (default: develop,main,master)");
                     println!("  -v, --verbose          Show detailed output");
                     println!("  -h, --help             Show this help message");
+                    println!("\nExit codes:");
+                    println!("  0  No threshold exceeded");
+                    println!("  1  Violations exceed a --fail-on or --max-* threshold");
+                    println!("  2  Usage error (arguments, credentials or config)");
+                    println!("  3  A repository could not be analyzed (beyond --max-errors), or the run aborted");
                     std::process::exit(0);
                 }
                 "-V" | "--version" => {
@@ -50,10 +214,48 @@ impl Cli {
             }
         }
 
-        if cli.repos.is_empty() {
-            return Err(Error::Cli("--repos argument is required".to_string()));
+        if cli.repos.is_empty()
+            && cli.repos_file.is_none()
+            && cli.paths.is_empty()
+            && cli.azure_org.is_none()
+            && cli.github_org.is_none()
+        {
+            return Err(Error::Cli(
+                "--repos, --repos-file, --path, --azure-org or --github-org argument is required"
+                    .to_string(),
+            ));
+        }
+        if !cli.azure_projects.is_empty() && cli.azure_org.is_none() {
+            return Err(Error::Cli("--project requires --azure-org".to_string()));
+        }
+        if !cli.topics.is_empty() && cli.github_org.is_none() {
+            return Err(Error::Cli("--topic requires --github-org".to_string()));
+        }
+
+        let outputs = cli.report_outputs();
+        for (i, output) in outputs.iter().enumerate() {
+            if outputs[..i].iter().any(|other| other.path == output.path) {
+                return Err(Error::Cli(format!(
+                    "Report path {} is used more than once",
+                    output.path.display()
+                )));
+            }
         }
 
         Ok(cli)
     }
+
+    /// The reports to write. `--format`/`--output` describe one report, which
+    /// defaults to markdown unless only `--report` outputs are requested.
+    pub fn report_outputs(&self) -> Vec<ReportOutput> {
+        let mut outputs = Vec::new();
+        if self.reports.is_empty() || self.format.is_some() || self.output.is_some() {
+            outputs.push(ReportOutput::new(
+                self.format.unwrap_or(RENDERERS[0]),
+                self.output.clone(),
+            ));
+        }
+        outputs.extend(self.reports.iter().cloned());
+        outputs
+    }
 }
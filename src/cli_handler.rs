@@ -1,14 +1,16 @@
 use crate::{
-    analyzer::analyze_pipelines,
+    analyzer::{analyze_pipelines, expand_branches, AnalysisOptions},
     cli::Cli,
     config::{Config, Credentials},
+    discovery::{AzureDevOpsDiscovery, GitHubDiscovery},
     error::{Error, Result},
-    report::generate_markdown_report,
+    gate::Gate,
+    inventory::{Inventory, RepoEntry},
 };
-use std::fs;
 
-pub fn handle_cli(cli: &Cli) -> Result<()> {
-    let credentials = Credentials::load(&cli.credentials)?;
+/// Runs the analysis and returns the process exit code.
+pub fn handle_cli(cli: &Cli) -> Result<i32> {
+    let credentials = Credentials::load_optional(&cli.credentials)?;
 
     if cli.verbose {
         println!("Loading configuration...");
@@ -16,32 +18,82 @@ pub fn handle_cli(cli: &Cli) -> Result<()> {
 
     let config = Config::load(cli.config_path.as_deref())?;
 
-    let repos: Vec<String> = cli
-        .repos
-        .split(',')
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .map(String::from)
-        .collect();
+    let mut inventory = Inventory::from_list(&cli.repos);
+    for path in &cli.paths {
+        inventory.repos.push(RepoEntry::local(path)?);
+    }
+    if let Some(path) = &cli.repos_file {
+        inventory.extend(Inventory::load(path)?);
+    }
+    if let Some(organization) = &cli.azure_org {
+        let api_credentials = Credentials::require(credentials.as_ref())?;
+        let mut discovery = AzureDevOpsDiscovery::new(organization)
+            .with_projects(&cli.azure_projects)
+            .with_filter(cli.filter.clone());
+        if let Some(base_url) = &cli.azure_url {
+            discovery = discovery.with_base_url(base_url);
+        }
+        inventory
+            .repos
+            .extend(discovery.discover(api_credentials, cli.verbose)?);
+    }
+    if let Some(organization) = &cli.github_org {
+        // An explicit --credentials wins over the ambient GITHUB_TOKEN
+        let token = match &cli.credentials {
+            Some(_) => credentials.as_ref().map(|c| c.token.clone()),
+            None => std::env::var("GITHUB_TOKEN").ok(),
+        };
+        let mut discovery = GitHubDiscovery::new(organization)
+            .with_token(token.as_deref())
+            .with_topics(&cli.topics)
+            .with_filter(cli.filter.clone());
+        if let Some(api_url) = &cli.github_url {
+            discovery = discovery.with_api_url(api_url);
+        }
+        inventory.repos.extend(discovery.discover(cli.verbose)?);
+    }
+    let entries = inventory.entries();
 
-    if repos.is_empty() {
+    if entries.is_empty() {
         return Err(Error::Config("No repositories specified".to_string()));
     }
 
-    if cli.verbose {
-        println!("Analyzing {} repositories...", repos.len());
+    // Only remote repositories need credentials
+    for entry in entries.iter().filter(|entry| entry.path.is_none()) {
+        Credentials::for_remote(credentials.as_ref(), &entry.url, cli.github_url.as_deref())?;
     }
 
-    let issues = analyze_pipelines(&repos, &credentials, &config, cli.verbose)?;
-
-    let report = generate_markdown_report(&repos, &config, &issues)?;
+    let options = AnalysisOptions {
+        verbose: cli.verbose,
+        jobs: cli.jobs,
+        fallback_branches: cli.fallback_branches.clone(),
+        github_url: cli.github_url.clone(),
+    };
+    let entries = expand_branches(&entries, &cli.branches, credentials.as_ref(), &options)?;
+    let repos: Vec<String> = entries.iter().map(RepoEntry::identity).collect();
+    let mut issues = analyze_pipelines(&entries, credentials.as_ref(), &config, &options)?;
+    for entry in &entries {
+        issues.add_repo_metadata(entry);
+    }
 
-    let output_path = "report.md";
-    if cli.verbose {
-        println!("Writing report to {}", output_path);
+    for output in cli.report_outputs() {
+        if cli.verbose {
+            println!(
+                "Writing {} report to {}",
+                output.renderer.name(),
+                output.path.display()
+            );
+        }
+        output.write(&repos, &config, &issues)?;
     }
 
-    fs::write(output_path, report)?;
+    let violations = cli.gate.evaluate(&issues);
+    for violation in &violations {
+        println!("❌ {}", violation);
+    }
+    if !cli.gate.is_empty() && violations.is_empty() {
+        println!("✅ All thresholds passed");
+    }
 
-    Ok(())
+    Ok(Gate::exit_code(&violations))
 }
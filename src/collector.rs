@@ -1,4 +1,7 @@
-use crate::{error::Result, find_pipeline_files, parse_task_definition};
+use crate::{
+    azure_pipeline::TaskStep, detect_platform, error::Result, find_pipeline_files, AzurePipeline,
+    GitHubWorkflow, GitLabPipeline, Platform,
+};
 use std::path::PathBuf;
 
 #[derive(Debug)]
@@ -7,6 +10,9 @@ pub enum CollectedTask {
         task_name: String,
         version: String,
         file_path: PathBuf,
+        line: usize,
+        column: usize,
+        source: String,
     },
 }
 
@@ -30,20 +36,35 @@ impl TaskImplementationCollector {
         for pipeline_file in pipeline_files {
             let content = std::fs::read_to_string(&pipeline_file)?;
 
-            let lines: Vec<&str> = content
-                .lines()
-                .map(|line| line.trim())
-                .filter(|line| !line.starts_with('#') && !line.starts_with("//"))
-                .collect();
-
-            for line in lines {
-                if let Some(task) = parse_task_definition(line) {
-                    collected.push(CollectedTask::Regular {
-                        task_name: task.name,
-                        version: task.version,
-                        file_path: pipeline_file.clone(),
-                    });
-                }
+            let relative = pipeline_file
+                .strip_prefix(&self.repo_path)
+                .unwrap_or(&pipeline_file);
+
+            // Files that are not valid YAML cannot contain pipeline tasks
+            let tasks: Vec<TaskStep> = match detect_platform(relative) {
+                Platform::AzurePipelines => match AzurePipeline::parse(&content) {
+                    Ok(pipeline) => pipeline.tasks().cloned().collect(),
+                    Err(_) => continue,
+                },
+                Platform::GitHubActions => match GitHubWorkflow::parse(&content) {
+                    Ok(workflow) => workflow.tasks().cloned().collect(),
+                    Err(_) => continue,
+                },
+                Platform::GitLabCi => match GitLabPipeline::parse(&content) {
+                    Ok(pipeline) => pipeline.tasks().cloned().collect(),
+                    Err(_) => continue,
+                },
+            };
+
+            for task in tasks {
+                collected.push(CollectedTask::Regular {
+                    task_name: task.name,
+                    version: task.version,
+                    file_path: pipeline_file.clone(),
+                    line: task.line,
+                    column: task.column,
+                    source: task.source,
+                });
             }
         }
 
@@ -1,10 +1,12 @@
 use crate::error::{Error, Result};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 
+use crate::url_provider::split_host_and_path;
 use crate::yaml_parser::YamlConfig;
-use crate::SupportedTask;
+use crate::{SupportedTask, TaskStatus};
 
 #[derive(Debug, Clone)]
 pub struct Credentials {
@@ -15,17 +17,68 @@ pub struct Credentials {
 // First try to load from CLI, then from environment variables, then from .env file
 impl Credentials {
     pub fn load(cli_credentials: &Option<String>) -> Result<Self> {
+        Self::require(Self::load_optional(cli_credentials)?.as_ref()).cloned()
+    }
+
+    /// Fails with the usual "not found" error when credentials are needed
+    /// but none were given.
+    pub fn require(credentials: Option<&Self>) -> Result<&Self> {
+        credentials.ok_or_else(|| {
+            Error::Config(
+                "Credentials not found. Please provide them via CLI or environment variables"
+                    .to_string(),
+            )
+        })
+    }
+
+    /// Like `load`, but `None` when no credentials are given at all, for runs
+    /// that do not fetch anything. Malformed credentials are still an error.
+    pub fn load_optional(cli_credentials: &Option<String>) -> Result<Option<Self>> {
         if let Some(creds_str) = cli_credentials {
-            Self::from_string(creds_str)
+            Self::from_string(creds_str).map(Some)
         } else if let (Ok(username), Ok(token)) =
             (env::var("AZURE_USERNAME"), env::var("AZURE_TOKEN"))
         {
-            Ok(Credentials { username, token })
+            Ok(Some(Credentials { username, token }))
         } else {
-            Err(Error::Config(
-                "Credentials not found. Please provide them via CLI or environment variables"
-                    .to_string(),
-            ))
+            Ok(None)
+        }
+    }
+
+    /// The credentials to fetch `repo_url` with: the given ones, or else
+    /// `GITHUB_TOKEN` for remotes on github.com or on the host of `github_url`
+    /// (the `--github-url` API of a GitHub Enterprise Server), so a CI token is
+    /// never sent to other hosts.
+    pub fn for_remote(
+        credentials: Option<&Self>,
+        repo_url: &str,
+        github_url: Option<&str>,
+    ) -> Result<Self> {
+        Self::for_remote_with(credentials, repo_url, github_url, |name| {
+            env::var(name).ok()
+        })
+    }
+
+    /// `for_remote` with the environment read through `env`.
+    pub fn for_remote_with(
+        credentials: Option<&Self>,
+        repo_url: &str,
+        github_url: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        if let Some(credentials) = credentials {
+            return Ok(credentials.clone());
+        }
+        let host = split_host_and_path(repo_url).0;
+        let is_github = host == "github.com"
+            || github_url.is_some_and(|api_url| split_host_and_path(api_url).0 == host);
+        let github_token = env("GITHUB_TOKEN").filter(|_| is_github);
+        match github_token {
+            Some(token) => Ok(Credentials {
+                username: "x-access-token".to_string(),
+                token,
+            }),
+            None => Self::require(None).cloned(),
         }
     }
 
@@ -46,53 +99,214 @@ impl Credentials {
 
 pub trait VersionCompare {
     fn version_matches(&self, other: &str) -> bool;
+
+    /// Orders two numeric versions (`2` == `2.0.0` < `2.1`). Returns `None`
+    /// when either side is not numeric, e.g. a branch name.
+    fn version_cmp(&self, other: &str) -> Option<Ordering>;
+}
+
+// Split versions into numeric components, ignoring a `v` prefix
+fn parse_numeric_version(version: &str) -> Option<Vec<u32>> {
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    version.split('.').map(|n| n.parse::<u32>().ok()).collect()
+}
+
+impl VersionCompare for str {
+    fn version_matches(&self, other: &str) -> bool {
+        match self.version_cmp(other) {
+            Some(ordering) => ordering == Ordering::Equal,
+            // One or both are invalid, fall back to string comparison
+            None => self == other,
+        }
+    }
+
+    fn version_cmp(&self, other: &str) -> Option<Ordering> {
+        let v1 = parse_numeric_version(self)?;
+        let v2 = parse_numeric_version(other)?;
+
+        // Both are valid version numbers, pad and compare
+        let max_len = v1.len().max(v2.len());
+        let pad = |v: Vec<u32>| -> Vec<u32> {
+            v.into_iter()
+                .chain(std::iter::repeat(0))
+                .take(max_len)
+                .collect()
+        };
+        Some(pad(v1).cmp(&pad(v2)))
+    }
 }
 
 impl VersionCompare for String {
     fn version_matches(&self, other: &str) -> bool {
-        // Split versions into numeric components
-        let parse_version = |v: &str| {
-            let parts: Vec<_> = v.split('.').collect();
-            // Check if all parts are valid numbers
-            if parts.iter().all(|n| n.parse::<u32>().is_ok()) {
-                Some(
-                    parts
+        self.as_str().version_matches(other)
+    }
+
+    fn version_cmp(&self, other: &str) -> Option<Ordering> {
+        self.as_str().version_cmp(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+/// An entry of `task_versions`: a plain version (`'2'`), a range
+/// (`'>=3, <5'`, `'^2'`, `'~1.4'`) or `'*'` for any version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionRequirement {
+    raw: String,
+    comparators: Vec<(Comparison, String)>,
+}
+
+impl VersionRequirement {
+    pub fn parse(text: &str) -> Result<Self> {
+        let raw = text.trim().to_string();
+        let invalid = || Error::Config(format!("Invalid version requirement '{}'", raw));
+
+        if raw.is_empty() {
+            return Err(invalid());
+        }
+        if raw == "*" {
+            return Ok(Self {
+                raw,
+                comparators: Vec::new(),
+            });
+        }
+        if !raw.starts_with(['<', '>', '=', '^', '~']) && !raw.contains(',') {
+            // Plain versions keep the exact-match behaviour of `version_matches`
+            return Ok(Self {
+                comparators: vec![(Comparison::Equal, raw.clone())],
+                raw,
+            });
+        }
+
+        let mut comparators = Vec::new();
+        for part in raw.split(',').map(str::trim) {
+            let (operator, version) = [">=", "<=", ">", "<", "=", "^", "~"]
+                .iter()
+                .find_map(|op| part.strip_prefix(op).map(|rest| (*op, rest)))
+                .unwrap_or(("=", part));
+            let version = version.trim();
+            let numbers = parse_numeric_version(version).ok_or_else(invalid)?;
+
+            match operator {
+                "^" => {
+                    // Compatible with the left-most non-zero component
+                    let significant = numbers
                         .iter()
-                        .map(|n| n.parse::<u32>().unwrap())
-                        .collect::<Vec<_>>(),
-                )
-            } else {
-                None
+                        .position(|n| *n != 0)
+                        .unwrap_or(numbers.len() - 1);
+                    comparators.push((Comparison::GreaterOrEqual, version.to_string()));
+                    comparators.push((Comparison::Less, bump(&numbers, significant)?));
+                }
+                "~" => {
+                    // Patch-level changes, or minor-level when only a major is given
+                    let level = if numbers.len() > 1 { 1 } else { 0 };
+                    comparators.push((Comparison::GreaterOrEqual, version.to_string()));
+                    comparators.push((Comparison::Less, bump(&numbers, level)?));
+                }
+                ">=" => comparators.push((Comparison::GreaterOrEqual, version.to_string())),
+                "<=" => comparators.push((Comparison::LessOrEqual, version.to_string())),
+                ">" => comparators.push((Comparison::Greater, version.to_string())),
+                "<" => comparators.push((Comparison::Less, version.to_string())),
+                _ => comparators.push((Comparison::Equal, version.to_string())),
             }
-        };
+        }
 
-        match (parse_version(self), parse_version(other)) {
-            (Some(v1), Some(v2)) => {
-                // Both are valid version numbers, pad and compare
-                let max_len = v1.len().max(v2.len());
-                let v1_padded: Vec<u32> = v1
-                    .into_iter()
-                    .chain(std::iter::repeat(0))
-                    .take(max_len)
-                    .collect();
-                let v2_padded: Vec<u32> = v2
-                    .into_iter()
-                    .chain(std::iter::repeat(0))
-                    .take(max_len)
-                    .collect();
-                v1_padded == v2_padded
-            }
-            _ => {
-                // One or both are invalid, fall back to string comparison
-                self == other
-            }
+        Ok(Self { raw, comparators })
+    }
+
+    pub fn matches(&self, version: &str) -> bool {
+        self.comparators
+            .iter()
+            .all(|(comparison, bound)| match comparison {
+                Comparison::Equal => version.version_matches(bound),
+                _ => match version.version_cmp(bound) {
+                    Some(ordering) => match comparison {
+                        Comparison::Less => ordering == Ordering::Less,
+                        Comparison::LessOrEqual => ordering != Ordering::Greater,
+                        Comparison::Greater => ordering == Ordering::Greater,
+                        _ => ordering != Ordering::Less,
+                    },
+                    None => false,
+                },
+            })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+}
+
+impl std::fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// Increments the component at `level` and drops the ones after it.
+fn bump(numbers: &[u32], level: usize) -> Result<String> {
+    let mut bumped: Vec<u32> = numbers.iter().take(level + 1).copied().collect();
+    bumped[level] = bumped[level].checked_add(1).ok_or_else(|| {
+        Error::Config(format!(
+            "Version component {} is too large for a range",
+            bumped[level]
+        ))
+    })?;
+    Ok(bumped
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+/// How a configured task is enforced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TaskPolicy {
+    /// Must be present in every repository, with a valid version.
+    Required,
+    /// Version is checked only where the task is used.
+    #[default]
+    Governed,
+    /// Must not appear in any repository.
+    Forbidden,
+}
+
+impl std::str::FromStr for TaskPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "required" => Ok(TaskPolicy::Required),
+            "governed" => Ok(TaskPolicy::Governed),
+            "forbidden" => Ok(TaskPolicy::Forbidden),
+            other => Err(format!(
+                "Unknown policy '{}'. Expected required, governed or forbidden",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for TaskPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskPolicy::Required => write!(f, "required"),
+            TaskPolicy::Governed => write!(f, "governed"),
+            TaskPolicy::Forbidden => write!(f, "forbidden"),
         }
     }
 }
 
 #[derive(Debug)]
 pub struct Config {
-    pub task_versions: HashMap<String, Vec<String>>,
+    pub task_versions: HashMap<String, Vec<VersionRequirement>>,
+    pub task_policies: HashMap<String, TaskPolicy>,
 }
 
 impl Config {
@@ -106,9 +320,18 @@ impl Config {
             )));
         }
 
-        let yaml_config = YamlConfig::load_from_file(path)?;
+        Self::from_yaml_config(YamlConfig::load_from_file(path)?)
+    }
+
+    /// Parses a configuration from YAML, as written in `ciprobeconfig.yml`.
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        Self::from_yaml_config(YamlConfig::parse(content)?)
+    }
+
+    fn from_yaml_config(yaml_config: YamlConfig) -> Result<Self> {
         let mut config = Config {
             task_versions: yaml_config.task_versions,
+            task_policies: yaml_config.task_policies,
         };
 
         config.normalize_task_names();
@@ -123,19 +346,58 @@ impl Config {
     }
 
     fn normalize_task_names(&mut self) {
-        let normalized_tasks: HashMap<String, Vec<String>> = self
+        let normalized_tasks: HashMap<String, Vec<VersionRequirement>> = self
             .task_versions
             .iter()
             .map(|(k, v)| (k.to_lowercase(), v.clone()))
             .collect();
 
         self.task_versions = normalized_tasks;
+
+        self.task_policies = self
+            .task_policies
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), *v))
+            .collect();
+    }
+
+    pub fn policy(&self, task_name: &str) -> TaskPolicy {
+        self.task_policies
+            .get(task_name)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Tasks every repository must contain.
+    pub fn get_required_tasks(&self) -> Vec<SupportedTask> {
+        self.get_all_tasks()
+            .into_iter()
+            .filter(|task| self.policy(&task.to_string()) == TaskPolicy::Required)
+            .collect()
+    }
+
+    /// Checks `version` of the (normalized) `task_name` against its policy
+    /// and allowed versions.
+    pub fn validate(&self, task_name: &str, version: &str) -> TaskStatus {
+        if self.policy(task_name) == TaskPolicy::Forbidden {
+            return TaskStatus::Forbidden;
+        }
+        match self.task_versions.get(task_name) {
+            Some(requirements) if !requirements.is_empty() => {
+                if requirements.iter().any(|r| r.matches(version)) {
+                    TaskStatus::Valid
+                } else {
+                    TaskStatus::Invalid
+                }
+            }
+            _ => TaskStatus::Unknown,
+        }
     }
 
     pub fn get_valid_versions(&self, task_name: &str) -> Vec<&str> {
         self.task_versions
             .get(task_name)
-            .map(|v| v.iter().map(|s| s.as_str()).collect())
+            .map(|v| v.iter().map(VersionRequirement::as_str).collect())
             .unwrap_or_default()
     }
 }
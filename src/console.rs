@@ -0,0 +1,50 @@
+use std::cell::RefCell;
+use std::io::Write;
+
+thread_local! {
+    static CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Prints a line of progress output like `println!`. Inside `capture` the line
+/// is buffered instead, so that parallel workers can print each repository's
+/// output as one block.
+#[macro_export]
+macro_rules! console {
+    () => {
+        $crate::console::write_line(String::new())
+    };
+    ($($arg:tt)*) => {
+        $crate::console::write_line(format!($($arg)*))
+    };
+}
+
+#[doc(hidden)]
+pub fn write_line(line: String) {
+    let captured = CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(buffer) => {
+            buffer.push_str(&line);
+            buffer.push('\n');
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        println!("{}", line);
+    }
+}
+
+/// Runs `f` with the `console!` output of this thread buffered, and returns
+/// the result with the output.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let previous = CAPTURE.with(|capture| capture.borrow_mut().replace(String::new()));
+    let result = f();
+    let output = CAPTURE.with(|capture| std::mem::replace(&mut *capture.borrow_mut(), previous));
+    (result, output.unwrap_or_default())
+}
+
+/// Prints captured output in one piece.
+pub fn flush(output: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(output.as_bytes());
+    let _ = stdout.flush();
+}
@@ -0,0 +1,300 @@
+use crate::{
+    console,
+    error::{Error, Result},
+    glob::glob_match,
+    http,
+    json::JsonValue,
+    Credentials, RepoEntry,
+};
+
+/// Name patterns (`*` and `?`) that select discovered repositories.
+#[derive(Debug, Clone, Default)]
+pub struct RepoFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl RepoFilter {
+    /// Whether a repository known by any of `names` (e.g. `repo` and
+    /// `project/repo`) passes: it matches an include pattern, if there are
+    /// any, and no exclude pattern.
+    pub fn matches(&self, names: &[&str]) -> bool {
+        let any_match = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| names.iter().any(|name| glob_match(pattern, name)))
+        };
+        (self.include.is_empty() || any_match(&self.include)) && !any_match(&self.exclude)
+    }
+}
+
+/// Lists the Git repositories of an Azure DevOps organization, or of some of
+/// its projects, through the REST API.
+#[derive(Debug, Clone)]
+pub struct AzureDevOpsDiscovery {
+    base_url: String,
+    organization: String,
+    projects: Vec<String>,
+    filter: RepoFilter,
+}
+
+impl AzureDevOpsDiscovery {
+    pub const DEFAULT_BASE_URL: &'static str = "https://dev.azure.com";
+
+    pub fn new(organization: &str) -> Self {
+        Self {
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+            organization: organization.to_string(),
+            projects: Vec::new(),
+            filter: RepoFilter::default(),
+        }
+    }
+
+    /// Points to another server, such as Azure DevOps Server or a test stand-in.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Limits discovery to these projects instead of the whole organization.
+    pub fn with_projects(mut self, projects: &[String]) -> Self {
+        self.projects = projects.to_vec();
+        self
+    }
+
+    pub fn with_filter(mut self, filter: RepoFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the enabled, non-empty repositories that pass the filter,
+    /// sorted by project and name.
+    pub fn discover(&self, credentials: &Credentials, verbose: bool) -> Result<Vec<RepoEntry>> {
+        console!(
+            "🔎 Discovering repositories in Azure DevOps organization {}...",
+            self.organization
+        );
+
+        let scopes: Vec<Option<&str>> = if self.projects.is_empty() {
+            vec![None]
+        } else {
+            self.projects.iter().map(|p| Some(p.as_str())).collect()
+        };
+
+        let user = format!("{}:{}", credentials.username, credentials.token);
+        let mut repos = Vec::new();
+        let mut skipped = 0;
+        for project in scopes {
+            let scope = match project {
+                Some(project) => format!("{}/{}", self.organization, project),
+                None => self.organization.clone(),
+            };
+            let url = format!(
+                "{}/{}/_apis/git/repositories?api-version=7.1",
+                self.base_url,
+                scope.replace(' ', "%20")
+            );
+            let response = http::get(&url, Some(&user), &[("Accept", "application/json")])?;
+            let document = JsonValue::parse(&response.body)?;
+            let items = document
+                .get("value")
+                .and_then(|v| v.as_array())
+                .ok_or_else(|| Error::Api(format!("Unexpected response from {}", url)))?;
+
+            for item in items {
+                let field = |key: &str| item.get(key).and_then(|v| v.as_str());
+                let (Some(name), Some(web_url)) =
+                    (field("name"), field("webUrl").or(field("remoteUrl")))
+                else {
+                    continue;
+                };
+                let project_name = item
+                    .get("project")
+                    .and_then(|p| p.get("name"))
+                    .and_then(|n| n.as_str())
+                    .unwrap_or_default();
+                let qualified_name = format!("{}/{}", project_name, name);
+
+                let reason = if item.get("isDisabled").and_then(|d| d.as_bool()) == Some(true) {
+                    Some("disabled")
+                } else if field("defaultBranch").is_none() {
+                    Some("empty")
+                } else if !self.filter.matches(&[name, &qualified_name]) {
+                    Some("filtered")
+                } else {
+                    None
+                };
+
+                match reason {
+                    Some(reason) => {
+                        if verbose {
+                            console!("   └─ ⏭️ Skipping {} ({})", qualified_name, reason);
+                        }
+                        skipped += 1;
+                    }
+                    None => repos.push((qualified_name, RepoEntry::new(web_url))),
+                }
+            }
+        }
+
+        repos.sort_by(|a, b| a.0.cmp(&b.0));
+        console!(
+            "   └─ Found {} repositories ({} skipped)",
+            repos.len(),
+            skipped
+        );
+        Ok(repos.into_iter().map(|(_, entry)| entry).collect())
+    }
+}
+
+/// Lists the repositories of a GitHub organization through the REST API.
+#[derive(Debug, Clone)]
+pub struct GitHubDiscovery {
+    api_url: String,
+    organization: String,
+    token: Option<String>,
+    topics: Vec<String>,
+    filter: RepoFilter,
+}
+
+impl GitHubDiscovery {
+    pub const DEFAULT_API_URL: &'static str = "https://api.github.com";
+
+    pub fn new(organization: &str) -> Self {
+        Self {
+            api_url: Self::DEFAULT_API_URL.to_string(),
+            organization: organization.to_string(),
+            token: None,
+            topics: Vec::new(),
+            filter: RepoFilter::default(),
+        }
+    }
+
+    /// Points to another API, such as `https://github.example.com/api/v3` for
+    /// GitHub Enterprise Server or a test stand-in.
+    pub fn with_api_url(mut self, api_url: &str) -> Self {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_token(mut self, token: Option<&str>) -> Self {
+        self.token = token.filter(|t| !t.is_empty()).map(String::from);
+        self
+    }
+
+    /// Only keeps repositories that have all of these topics.
+    pub fn with_topics(mut self, topics: &[String]) -> Self {
+        self.topics = topics.iter().map(|t| t.to_lowercase()).collect();
+        self
+    }
+
+    pub fn with_filter(mut self, filter: RepoFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the repositories that are neither archived nor forks and pass
+    /// the topic and name filters, sorted by name. Follows the `Link` header
+    /// through every page of results.
+    pub fn discover(&self, verbose: bool) -> Result<Vec<RepoEntry>> {
+        console!(
+            "🔎 Discovering repositories in GitHub organization {}...",
+            self.organization
+        );
+
+        let authorization = self.token.as_ref().map(|t| format!("Bearer {}", t));
+        let mut headers = vec![
+            ("Accept", "application/vnd.github+json"),
+            ("X-GitHub-Api-Version", "2022-11-28"),
+            ("User-Agent", "ciprobe"),
+        ];
+        if let Some(authorization) = &authorization {
+            headers.push(("Authorization", authorization));
+        }
+
+        let mut next = Some(format!(
+            "{}/orgs/{}/repos?type=all&per_page=100",
+            self.api_url, self.organization
+        ));
+        let mut repos = Vec::new();
+        let mut skipped = 0;
+        while let Some(url) = next.take() {
+            let response = http::get(&url, None, &headers)?;
+            let document = JsonValue::parse(&response.body)?;
+            let items = document
+                .as_array()
+                .ok_or_else(|| Error::Api(format!("Unexpected response from {}", url)))?;
+
+            for item in items {
+                let field = |key: &str| item.get(key).and_then(|v| v.as_str());
+                let flag = |key: &str| item.get(key).and_then(|v| v.as_bool()) == Some(true);
+                let (Some(name), Some(full_name), Some(html_url)) =
+                    (field("name"), field("full_name"), field("html_url"))
+                else {
+                    continue;
+                };
+                let topics: Vec<_> = item
+                    .get("topics")
+                    .and_then(|t| t.as_array())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|t| t.as_str())
+                    .map(str::to_lowercase)
+                    .collect();
+
+                let reason = if flag("archived") {
+                    Some("archived")
+                } else if flag("fork") {
+                    Some("fork")
+                } else if !self.topics.iter().all(|t| topics.contains(t)) {
+                    Some("topics")
+                } else if !self.filter.matches(&[name, full_name]) {
+                    Some("filtered")
+                } else {
+                    None
+                };
+
+                match reason {
+                    Some(reason) => {
+                        if verbose {
+                            console!("   └─ ⏭️ Skipping {} ({})", full_name, reason);
+                        }
+                        skipped += 1;
+                    }
+                    None => {
+                        let mut entry = RepoEntry::new(html_url);
+                        entry.tags = topics;
+                        repos.push((full_name.to_string(), entry));
+                    }
+                }
+            }
+
+            next = response.header("Link").and_then(next_page);
+        }
+
+        repos.sort_by(|a, b| a.0.cmp(&b.0));
+        console!(
+            "   └─ Found {} repositories ({} skipped)",
+            repos.len(),
+            skipped
+        );
+        Ok(repos.into_iter().map(|(_, entry)| entry).collect())
+    }
+}
+
+/// The `rel="next"` URL of a `Link` header:
+/// `<https://api.github.com/...&page=2>; rel="next", <...>; rel="last"`.
+fn next_page(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
@@ -5,6 +5,8 @@ pub enum Error {
     Cli(String),
     Config(String),
     Git(String),
+    Parse(String),
+    Api(String),
     Io(std::io::Error),
 }
 
@@ -16,6 +18,8 @@ impl fmt::Display for Error {
             Error::Cli(msg) => write!(f, "CLI error: {}", msg),
             Error::Config(msg) => write!(f, "Configuration error: {}", msg),
             Error::Git(msg) => write!(f, "Git error: {}", msg),
+            Error::Parse(msg) => write!(f, "Parse error: {}", msg),
+            Error::Api(msg) => write!(f, "API error: {}", msg),
             Error::Io(err) => write!(f, "IO error: {}", err),
         }
     }
@@ -0,0 +1,173 @@
+use crate::error::{Error, Result};
+use crate::TaskIssues;
+use std::collections::BTreeMap;
+
+/// Nothing exceeded its threshold.
+pub const EXIT_SUCCESS: i32 = 0;
+/// At least one check exceeded its threshold.
+pub const EXIT_VIOLATIONS: i32 = 1;
+/// Invalid arguments, credentials or configuration.
+pub const EXIT_USAGE_ERROR: i32 = 2;
+/// A repository could not be analyzed (beyond what `--max-errors` allows), or
+/// the run aborted.
+pub const EXIT_ANALYSIS_ERROR: i32 = 3;
+
+/// A category of findings that can fail the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GateCheck {
+    /// Task implementations whose version is not allowed.
+    Invalid,
+    /// Required tasks absent from a repository.
+    Missing,
+    /// Tasks used in pipelines but not listed in the configuration.
+    Unknown,
+    /// Usages of forbidden tasks.
+    Forbidden,
+    /// Repositories that could not be cloned or analyzed, and pipeline files
+    /// that could not be parsed.
+    Error,
+}
+
+impl GateCheck {
+    pub fn count(&self, issues: &TaskIssues) -> usize {
+        match self {
+            GateCheck::Invalid => issues
+                .invalid_states
+                .values()
+                .flat_map(|m| m.values())
+                .map(|v| v.len())
+                .sum(),
+            GateCheck::Missing => issues
+                .missing_required_tasks
+                .values()
+                .map(|v| v.len())
+                .sum(),
+            GateCheck::Unknown => issues.missing_states.len(),
+            GateCheck::Forbidden => issues.forbidden_usages.values().map(|v| v.len()).sum(),
+            GateCheck::Error => {
+                issues.repos_failed.len()
+                    + issues.files_failed.values().map(|v| v.len()).sum::<usize>()
+            }
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            GateCheck::Invalid => "invalid task versions",
+            GateCheck::Missing => "missing required tasks",
+            GateCheck::Unknown => "unknown tasks",
+            GateCheck::Forbidden => "forbidden task usages",
+            GateCheck::Error => "repositories or pipeline files that failed to analyze",
+        }
+    }
+}
+
+impl std::str::FromStr for GateCheck {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "invalid" => Ok(GateCheck::Invalid),
+            "missing" => Ok(GateCheck::Missing),
+            "unknown" => Ok(GateCheck::Unknown),
+            "forbidden" => Ok(GateCheck::Forbidden),
+            "error" | "errors" => Ok(GateCheck::Error),
+            other => Err(Error::Cli(format!(
+                "Unknown --fail-on value '{}'. Expected invalid, missing, unknown, forbidden or error",
+                other
+            ))),
+        }
+    }
+}
+
+/// A check whose count went over its threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GateViolation {
+    pub check: GateCheck,
+    pub count: usize,
+    pub max: usize,
+}
+
+impl std::fmt::Display for GateViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} (allowed: {})",
+            self.count,
+            self.check.description(),
+            self.max
+        )
+    }
+}
+
+/// Thresholds that turn a report into a pass/fail result. Checks without a
+/// threshold never fail the run, except that failed repositories do unless an
+/// `error` threshold is set.
+#[derive(Debug, Clone, Default)]
+pub struct Gate {
+    thresholds: BTreeMap<GateCheck, usize>,
+}
+
+impl Gate {
+    /// Fails the run on any finding of `check`.
+    pub fn fail_on(&mut self, check: GateCheck) {
+        self.max(check, 0);
+    }
+
+    /// Fails the run when `check` finds more than `max` items. The strictest
+    /// threshold wins when a check is configured more than once.
+    pub fn max(&mut self, check: GateCheck, max: usize) {
+        let threshold = self.thresholds.entry(check).or_insert(max);
+        *threshold = (*threshold).min(max);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    pub fn evaluate(&self, issues: &TaskIssues) -> Vec<GateViolation> {
+        let mut violations: Vec<_> = self
+            .thresholds
+            .iter()
+            .filter_map(|(check, max)| {
+                let count = check.count(issues);
+                (count > *max).then_some(GateViolation {
+                    check: *check,
+                    count,
+                    max: *max,
+                })
+            })
+            .collect();
+
+        // The report of a repository that failed is incomplete, so the run
+        // fails unless the failures are explicitly tolerated
+        if !self.thresholds.contains_key(&GateCheck::Error) && !issues.repos_failed.is_empty() {
+            violations.push(GateViolation {
+                check: GateCheck::Error,
+                count: issues.repos_failed.len(),
+                max: 0,
+            });
+        }
+        violations
+    }
+
+    /// Analysis errors take precedence over other violations, as the
+    /// remaining counts are incomplete when repositories failed.
+    pub fn exit_code(violations: &[GateViolation]) -> i32 {
+        if violations.iter().any(|v| v.check == GateCheck::Error) {
+            EXIT_ANALYSIS_ERROR
+        } else if violations.is_empty() {
+            EXIT_SUCCESS
+        } else {
+            EXIT_VIOLATIONS
+        }
+    }
+}
+
+/// Exit code for a run that aborted with `error`.
+pub fn error_exit_code(error: &Error) -> i32 {
+    match error {
+        Error::Cli(_) | Error::Config(_) => EXIT_USAGE_ERROR,
+        _ => EXIT_ANALYSIS_ERROR,
+    }
+}
@@ -1,75 +1,242 @@
 use crate::{
+    console,
     error::{Error, Result},
-    Credentials,
+    url_provider::redact_credentials,
+    Credentials, RepositoryUrl,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex, PoisonError},
 };
-use std::{path::PathBuf, process::Command};
 
 const SPARSE_PATTERNS: [&str; 2] = ["*.yml", "*.yaml"];
 
+/// Answers git's credential requests from the environment of the git process,
+/// so the token never appears in the remote URL, `.git/config` or the process
+/// arguments.
+const CREDENTIAL_HELPER: &str = "credential.helper=!f() { test \"$1\" = get && \
+    echo \"username=$CIPROBE_GIT_USERNAME\" && echo \"password=$CIPROBE_GIT_TOKEN\"; }; f";
+
+const REDACTED: &str = "***";
+
+/// One lock per checkout directory, so that parallel workers and shared
+/// template lookups never use the same checkout at once.
+static CHECKOUT_LOCKS: Mutex<BTreeMap<PathBuf, Arc<Mutex<()>>>> = Mutex::new(BTreeMap::new());
+
+/// Branches tried in order when the remote does not report its HEAD.
+pub const DEFAULT_FALLBACK_BRANCHES: [&str; 3] = ["develop", "main", "master"];
+
 pub struct GitManager {
     repo_url: String,
+    credentials: Credentials,
     repo_dir: PathBuf,
+    reference: Option<String>,
+    fallback_branches: Vec<String>,
     verbose: bool,
 }
 
 impl GitManager {
     pub fn new(credentials: Credentials, repo_url: &str, verbose: bool) -> Result<Self> {
-        let repo_name = repo_url
-            .split('/')
-            .last()
-            .unwrap_or("repo")
-            .trim_end_matches("/_git/")
-            .trim_end_matches(".git")
-            .replace("%20", " ");
-
-        let formatted_repo_url = if repo_url.contains("@") {
-            let parts: Vec<&str> = repo_url.splitn(2, '@').collect();
-            format!(
-                "https://{}:{}@{}",
-                credentials.username, credentials.token, parts[1]
-            )
+        let repo_name = RepositoryUrl::parse(repo_url).name;
+
+        // Credentials are passed by `remote_git`, never in the URL
+        let formatted_repo_url = if repo_url.starts_with("file://") {
+            repo_url.to_string()
         } else {
-            let url_part = repo_url.trim_start_matches("https://").replace(" ", "%20");
-            format!(
-                "https://{}:{}@{}",
-                credentials.username, credentials.token, url_part
-            )
+            let scheme = if repo_url.starts_with("http://") {
+                "http://"
+            } else {
+                "https://"
+            };
+            let url_part = match repo_url.split_once('@') {
+                Some((_, host_and_path)) => host_and_path.to_string(),
+                None => repo_url.trim_start_matches(scheme).replace(" ", "%20"),
+            };
+            format!("{}{}", scheme, url_part)
         };
 
+        // Repositories of the same name in other organizations or projects
+        // get their own checkout
         let repo_dir = std::env::current_dir()
             .map_err(Error::Io)?
             .join("temp_repos")
-            .join(&repo_name);
+            .join(format!(
+                "{}-{:016x}",
+                repo_name,
+                url_hash(&formatted_repo_url)
+            ));
 
         Ok(Self {
             repo_url: formatted_repo_url,
+            credentials,
             repo_dir,
+            reference: None,
+            fallback_branches: DEFAULT_FALLBACK_BRANCHES.map(String::from).to_vec(),
             verbose,
         })
     }
 
-    fn try_default_branches(repo_dir: &PathBuf) -> Result<String> {
-        for branch in ["develop", "main", "master"] {
-            if Self::try_fetch_branch(repo_dir, branch).is_ok() {
+    /// Checks out `reference` (a branch, tag or full ref) instead of the
+    /// default branch. Such checkouts get their own directory so they do not
+    /// clash with a default-branch clone of the same repository.
+    pub fn with_reference(mut self, reference: Option<&str>) -> Self {
+        if let Some(reference) = reference {
+            let dir_name = format!(
+                "{}@{}",
+                self.repo_dir
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("repo"),
+                reference.replace(['/', '\\', ':'], "_")
+            );
+            self.repo_dir.set_file_name(dir_name);
+            self.reference = Some(reference.to_string());
+        }
+        self
+    }
+
+    /// Runs `f` while no other thread uses this checkout directory.
+    pub fn with_lock<T>(&self, f: impl FnOnce(&Self) -> T) -> T {
+        let lock = CHECKOUT_LOCKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(self.repo_dir.clone())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+        f(self)
+    }
+
+    /// Replaces the branches guessed when the remote does not report its HEAD.
+    pub fn with_fallback_branches(mut self, branches: &[String]) -> Self {
+        if !branches.is_empty() {
+            self.fallback_branches = branches.to_vec();
+        }
+        self
+    }
+
+    /// A git command for talking to the remote. It authenticates through
+    /// `CREDENTIAL_HELPER`, replacing any helper configured by the user so the
+    /// token is not stored anywhere.
+    fn remote_git(&self) -> Command {
+        let mut command = Command::new("git");
+        command
+            .args(["-c", "credential.helper=", "-c", CREDENTIAL_HELPER])
+            .env("CIPROBE_GIT_USERNAME", &self.credentials.username)
+            .env("CIPROBE_GIT_TOKEN", &self.credentials.token)
+            .env("GIT_TERMINAL_PROMPT", "0");
+        command
+    }
+
+    /// Masks the token and any credentials in URLs, for git output that is
+    /// logged or reported.
+    fn redact(&self, text: &str) -> String {
+        let text = redact_credentials(text);
+        if self.credentials.token.is_empty() {
+            return text;
+        }
+        text.replace(&self.credentials.token, REDACTED)
+    }
+
+    /// The branches of the remote, read without cloning it.
+    pub fn remote_branches(&self) -> Result<Vec<String>> {
+        let output = self
+            .remote_git()
+            .args(["ls-remote", "--heads", &self.repo_url])
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(format!(
+                "Failed to list remote branches: {}",
+                self.redact(String::from_utf8_lossy(&output.stderr).trim())
+            )));
+        }
+
+        // <sha>	refs/heads/main
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once("refs/heads/"))
+            .map(|(_, branch)| branch.trim().to_string())
+            .collect())
+    }
+
+    /// The branch the remote HEAD points to, e.g. `main` or `trunk`.
+    fn remote_head(&self) -> Option<String> {
+        let output = self
+            .remote_git()
+            .args(["ls-remote", "--symref", "origin", "HEAD"])
+            .current_dir(&self.repo_dir)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        // ref: refs/heads/main	HEAD
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix("ref: ")?
+                    .split_whitespace()
+                    .next()?
+                    .strip_prefix("refs/heads/")
+                    .map(String::from)
+            })
+    }
+
+    /// Fetches the default branch of the remote, falling back to the first of
+    /// the fallback branches that exists.
+    fn fetch_default_branch(&self) -> Result<String> {
+        if let Some(branch) = self.remote_head() {
+            if self.verbose {
+                console!("Remote HEAD points to {}", branch);
+            }
+            return self.try_fetch_branch(&branch);
+        }
+
+        for branch in &self.fallback_branches {
+            if self.try_fetch_branch(branch).is_ok() {
                 return Ok(branch.to_string());
             }
         }
-        Err(Error::Git("No default branch found".to_string()))
+        Err(Error::Git(format!(
+            "No default branch found (tried {})",
+            self.fallback_branches.join(", ")
+        )))
     }
 
     pub fn get_repo_path(&self) -> &PathBuf {
         &self.repo_dir
     }
 
-    pub fn clone_or_update(&self) -> Result<()> {
-        if self.repo_dir.exists() {
+    /// The full SHA of the commit checked out in `repo_dir`, if it is a git
+    /// checkout.
+    pub fn head_commit(repo_dir: &Path) -> Option<String> {
+        let output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo_dir)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Fetches the repository and returns the branch (or ref) checked out.
+    pub fn clone_or_update(&self) -> Result<String> {
+        if let Some(reference) = &self.reference {
+            self.checkout_reference(reference)?;
+            Ok(reference.clone())
+        } else if self.repo_dir.exists() {
             self.update_repo()
         } else {
             self.clone_repo()
         }
     }
 
-    fn clone_repo(&self) -> Result<()> {
+    fn clone_repo(&self) -> Result<String> {
         let repo_name = self
             .repo_dir
             .file_name()
@@ -77,9 +244,35 @@ impl GitManager {
             .unwrap_or("unknown");
 
         if self.verbose {
-            println!("Cloning repository {}...", repo_name);
+            console!("Cloning repository {}...", repo_name);
         }
 
+        self.init_repo()?;
+
+        let default_branch = self.fetch_default_branch()?;
+
+        // Create and checkout the branch properly
+        Command::new("git")
+            .args([
+                "checkout",
+                "-b",
+                &default_branch,
+                &format!("origin/{}", default_branch),
+            ])
+            .current_dir(&self.repo_dir)
+            .output()?;
+
+        if self.verbose {
+            console!(
+                "✓ Successfully cloned repository {} with sparse checkout",
+                repo_name
+            );
+        }
+        Ok(default_branch)
+    }
+
+    /// Creates an empty repository with sparse checkout and the remote configured.
+    fn init_repo(&self) -> Result<()> {
         // Create the repository directory itself, not just the parent
         std::fs::create_dir_all(&self.repo_dir)?;
 
@@ -121,43 +314,88 @@ impl GitManager {
             return Err(Error::Git("Failed to add remote".to_string()));
         }
 
-        let default_branch = Self::try_default_branches(&self.repo_dir)?;
+        Ok(())
+    }
 
-        // Create and checkout the branch properly
-        Command::new("git")
-            .args([
-                "checkout",
-                "-b",
-                &default_branch,
-                &format!("origin/{}", default_branch),
-            ])
+    /// Points `origin` at the URL without credentials, also removing a token
+    /// that older versions stored in `.git/config`.
+    fn set_remote_url(&self) -> Result<()> {
+        let output = Command::new("git")
+            .args(["remote", "set-url", "origin", &self.repo_url])
             .current_dir(&self.repo_dir)
             .output()?;
 
+        if !output.status.success() {
+            return Err(Error::Git("Failed to set remote URL".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Fetches exactly `reference` and checks it out as a detached HEAD.
+    fn checkout_reference(&self, reference: &str) -> Result<()> {
+        if self.repo_dir.exists() {
+            self.set_remote_url()?;
+        } else {
+            self.init_repo()?;
+        }
+
         if self.verbose {
-            println!(
-                "✓ Successfully cloned repository {} with sparse checkout",
-                repo_name
-            );
+            console!("Fetching {} of {}...", reference, self.repo_dir.display());
         }
+
+        let output = self
+            .remote_git()
+            .args(["fetch", "--depth=1", "origin", reference])
+            .current_dir(&self.repo_dir)
+            .output()?;
+
+        if !output.status.success() {
+            // Servers only hand out commits by their full SHA
+            let hint = if is_short_sha(reference) {
+                " (commits need their full 40-character SHA)"
+            } else {
+                ""
+            };
+            return Err(Error::Git(format!(
+                "Failed to fetch {}{}: {}",
+                reference,
+                hint,
+                self.redact(String::from_utf8_lossy(&output.stderr).trim())
+            )));
+        }
+
+        let output = Command::new("git")
+            .args(["checkout", "--force", "--detach", "FETCH_HEAD"])
+            .current_dir(&self.repo_dir)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(Error::Git(format!("Failed to check out {}", reference)));
+        }
+
         Ok(())
     }
 
     // Helper function to try fetching a specific branch
-    fn try_fetch_branch(repo_dir: &PathBuf, branch_name: &str) -> Result<String> {
-        let output = Command::new("git")
+    fn try_fetch_branch(&self, branch_name: &str) -> Result<String> {
+        let output = self
+            .remote_git()
             .args(["fetch", "--depth=1", "origin", branch_name])
-            .current_dir(repo_dir)
+            .current_dir(&self.repo_dir)
             .output()?;
 
         if output.status.success() {
             Ok(branch_name.to_string())
         } else {
-            Err(Error::Git(format!("Branch {} not found", branch_name)))
+            Err(Error::Git(format!(
+                "Failed to fetch branch {}: {}",
+                branch_name,
+                self.redact(String::from_utf8_lossy(&output.stderr).trim())
+            )))
         }
     }
 
-    fn update_repo(&self) -> Result<()> {
+    fn update_repo(&self) -> Result<String> {
         let repo_name = self
             .repo_dir
             .file_name()
@@ -165,9 +403,11 @@ impl GitManager {
             .unwrap_or("unknown");
 
         if self.verbose {
-            println!("Repository {} exists, updating...", repo_name);
+            console!("Repository {} exists, updating...", repo_name);
         }
 
+        self.set_remote_url()?;
+
         // Get current branch name
         let branch_output = Command::new("git")
             .args(["rev-parse", "--abbrev-ref", "HEAD"])
@@ -178,9 +418,9 @@ impl GitManager {
             .trim()
             .to_string();
 
-        // If we're in detached HEAD state, try branches in order
-        if current_branch == "HEAD" {
-            let default_branch = Self::try_default_branches(&self.repo_dir)?;
+        // Switch when the default branch changed or HEAD is detached
+        let default_branch = self.fetch_default_branch()?;
+        if current_branch != default_branch {
             Self::try_checkout_branch(&self.repo_dir, &default_branch)?;
         }
 
@@ -207,7 +447,7 @@ impl GitManager {
             .output()?;
 
         if !reset_output.status.success() {
-            println!("✗ Failed to reset repository {}", repo_name);
+            console!("✗ Failed to reset repository {}", repo_name);
             return Err(Error::Git(format!(
                 "Failed to reset repository {}",
                 repo_name
@@ -215,15 +455,16 @@ impl GitManager {
         }
 
         // Pull latest changes
-        let pull_output = Command::new("git")
+        let pull_output = self
+            .remote_git()
             .args(["pull", "--force"])
             .current_dir(&self.repo_dir)
             .output()?;
 
         if !pull_output.status.success() {
             let error = String::from_utf8_lossy(&pull_output.stderr);
-            println!("✗ Failed to update repository {}", repo_name);
-            println!("Error: {}", error);
+            console!("✗ Failed to update repository {}", repo_name);
+            console!("Error: {}", self.redact(&error));
             return Err(Error::Git(format!(
                 "Failed to update repository {}",
                 repo_name
@@ -231,15 +472,20 @@ impl GitManager {
         }
 
         if self.verbose {
-            println!("✓ Successfully updated repository {}", repo_name);
+            console!("✓ Successfully updated repository {}", repo_name);
         }
-        Ok(())
+        Ok(default_branch)
     }
 
     // Helper function to try checking out a specific branch
     fn try_checkout_branch(repo_dir: &PathBuf, branch_name: &str) -> Result<()> {
         let output = Command::new("git")
-            .args(["checkout", branch_name])
+            .args([
+                "checkout",
+                "-B",
+                branch_name,
+                &format!("origin/{}", branch_name),
+            ])
             .current_dir(repo_dir)
             .output()?;
 
@@ -250,3 +496,15 @@ impl GitManager {
         }
     }
 }
+
+fn is_short_sha(reference: &str) -> bool {
+    (4..40).contains(&reference.len()) && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// FNV-1a hash of a remote URL; stable across runs and Rust versions, so
+/// checkouts are reused.
+fn url_hash(url: &str) -> u64 {
+    url.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
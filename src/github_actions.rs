@@ -0,0 +1,85 @@
+use crate::{
+    azure_pipeline::TaskStep,
+    error::Result,
+    line_parser::parse_action_reference,
+    yaml_document::{parse_documents, source_line, YamlNode},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    /// A `uses:` step inside a job or a composite action.
+    Step,
+    /// A job-level `uses:` calling a reusable workflow.
+    ReusableWorkflow,
+}
+
+/// A versioned `uses: owner/repo[/path]@ref` reference. The `owner/repo[/path]`
+/// part is treated as the task name and the ref as its version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionReference {
+    pub kind: ActionKind,
+    pub task: TaskStep,
+}
+
+/// The `uses:` references of a GitHub Actions workflow or composite `action.yml`.
+#[derive(Debug, Default)]
+pub struct GitHubWorkflow {
+    pub actions: Vec<ActionReference>,
+}
+
+impl GitHubWorkflow {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut workflow = GitHubWorkflow::default();
+
+        for document in parse_documents(content)? {
+            if let Some(jobs) = document.get("jobs").and_then(|j| j.as_mapping()) {
+                for (_, job) in jobs {
+                    if let Some(uses) = job.get("uses") {
+                        workflow.add_action(uses, ActionKind::ReusableWorkflow);
+                    }
+                    if let Some(steps) = job.get("steps") {
+                        workflow.walk_steps(steps);
+                    }
+                }
+            }
+
+            // Composite actions keep their steps under `runs`
+            if let Some(steps) = document.get("runs").and_then(|r| r.get("steps")) {
+                workflow.walk_steps(steps);
+            }
+        }
+
+        for action in &mut workflow.actions {
+            action.task.source = source_line(content, action.task.line);
+        }
+        Ok(workflow)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &TaskStep> {
+        self.actions.iter().map(|action| &action.task)
+    }
+
+    fn walk_steps(&mut self, steps: &YamlNode) {
+        for step in steps.as_sequence().unwrap_or_default() {
+            if let Some(uses) = step.get("uses") {
+                self.add_action(uses, ActionKind::Step);
+            }
+        }
+    }
+
+    fn add_action(&mut self, node: &YamlNode, kind: ActionKind) {
+        let Some(action) = node.as_str().and_then(parse_action_reference) else {
+            return;
+        };
+        self.actions.push(ActionReference {
+            kind,
+            task: TaskStep {
+                name: action.name,
+                version: action.version,
+                line: node.line,
+                column: node.column,
+                source: String::new(),
+            },
+        });
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    azure_pipeline::TaskStep,
+    error::Result,
+    line_parser::{parse_component_reference, parse_image_reference, TaskDefinition},
+    yaml_document::{parse_documents, source_line, YamlNode, YamlValue},
+};
+
+/// Top-level keys of `.gitlab-ci.yml` that are not job definitions.
+const RESERVED_KEYS: [&str; 11] = [
+    "stages",
+    "variables",
+    "include",
+    "default",
+    "workflow",
+    "image",
+    "services",
+    "before_script",
+    "after_script",
+    "cache",
+    "spec",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitLabReferenceKind {
+    /// `include: project:` with its `ref:` (the project's default branch when omitted).
+    ProjectInclude,
+    /// `include: component: host/group/project/component@version`.
+    Component,
+    /// `image:` of the pipeline default or of a job.
+    Image,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitLabReference {
+    pub kind: GitLabReferenceKind,
+    pub task: TaskStep,
+}
+
+/// The versioned references of a GitLab CI configuration file.
+#[derive(Debug, Default)]
+pub struct GitLabPipeline {
+    pub references: Vec<GitLabReference>,
+}
+
+impl GitLabPipeline {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut pipeline = GitLabPipeline::default();
+
+        for document in parse_documents(content)? {
+            let Some(entries) = document.as_mapping() else {
+                continue;
+            };
+
+            if let Some(include) = document.get("include") {
+                pipeline.add_includes(include);
+            }
+            if let Some(image) = document.get("image") {
+                pipeline.add_image(image);
+            }
+            if let Some(image) = document.get("default").and_then(|d| d.get("image")) {
+                pipeline.add_image(image);
+            }
+
+            for (key, job) in entries {
+                if RESERVED_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+                if let Some(image) = job.get("image") {
+                    pipeline.add_image(image);
+                }
+            }
+        }
+
+        for reference in &mut pipeline.references {
+            reference.task.source = source_line(content, reference.task.line);
+        }
+        Ok(pipeline)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &TaskStep> {
+        self.references.iter().map(|reference| &reference.task)
+    }
+
+    fn add_includes(&mut self, include: &YamlNode) {
+        match &include.value {
+            YamlValue::Sequence(items) => {
+                for item in items {
+                    self.add_include(item);
+                }
+            }
+            _ => self.add_include(include),
+        }
+    }
+
+    fn add_include(&mut self, include: &YamlNode) {
+        if let Some(component) = include.get("component") {
+            let definition = component.as_str().and_then(parse_component_reference);
+            self.add(GitLabReferenceKind::Component, component, definition);
+        } else if let Some(project) = include.get("project") {
+            let definition = project.as_str().map(|name| TaskDefinition {
+                name: name.trim_matches('/').to_string(),
+                version: include
+                    .get("ref")
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("HEAD")
+                    .to_string(),
+            });
+            self.add(GitLabReferenceKind::ProjectInclude, project, definition);
+        }
+    }
+
+    fn add_image(&mut self, image: &YamlNode) {
+        let image = image.get("name").unwrap_or(image);
+        let definition = image.as_str().and_then(parse_image_reference);
+        self.add(GitLabReferenceKind::Image, image, definition);
+    }
+
+    fn add(
+        &mut self,
+        kind: GitLabReferenceKind,
+        node: &YamlNode,
+        definition: Option<TaskDefinition>,
+    ) {
+        let Some(definition) = definition else {
+            return;
+        };
+        self.references.push(GitLabReference {
+            kind,
+            task: TaskStep {
+                name: definition.name,
+                version: definition.version,
+                line: node.line,
+                column: node.column,
+                source: String::new(),
+            },
+        });
+    }
+}
@@ -0,0 +1,40 @@
+/// Matches `text` against a shell-style pattern where `*` matches any run of
+/// characters (including `/`) and `?` matches a single character. Matching is
+/// case-insensitive, like repository names on the hosting providers.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    glob_match_case_sensitive(&pattern.to_lowercase(), &text.to_lowercase())
+}
+
+/// Like `glob_match`, but case-sensitive, for names such as git branches
+/// where `release/*` must not select `Release/1.0`.
+pub fn glob_match_case_sensitive(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it is matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,427 @@
+use crate::{
+    error::Result,
+    gate::GateCheck,
+    json_report::path_string,
+    report::ReportRenderer,
+    report::{escape_xml as escape, utc_timestamp},
+    Config, RepositoryUrl, SupportedTask, TaskImplementation, TaskIssues, TaskPolicy, TaskStatus,
+};
+use std::collections::BTreeSet;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { margin-bottom: 0.2rem; }
+.muted { color: #656d76; }
+.cards { display: flex; gap: 1rem; flex-wrap: wrap; margin: 1.5rem 0; }
+.card { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.8rem 1.2rem; min-width: 8rem; }
+.card strong { display: block; font-size: 1.6rem; }
+.filters { display: flex; gap: 0.5rem; flex-wrap: wrap; margin: 1rem 0; position: sticky; top: 0; background: #fff; padding: 0.5rem 0; }
+.filters input, .filters select { padding: 0.3rem 0.5rem; border: 1px solid #d0d7de; border-radius: 4px; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2rem; font-size: 0.9rem; }
+th, td { border: 1px solid #d0d7de; padding: 0.35rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; cursor: pointer; user-select: none; white-space: nowrap; }
+th[data-order="asc"]::after { content: " ▲"; }
+th[data-order="desc"]::after { content: " ▼"; }
+.matrix { overflow-x: auto; }
+.matrix th, .matrix td { text-align: center; }
+.matrix td:first-child, .matrix th:first-child { text-align: left; }
+.status { border-radius: 4px; padding: 0 0.4rem; white-space: nowrap; }
+.valid { background: #dafbe1; }
+.invalid, .missing, .forbidden, .failed { background: #ffebe9; }
+.unknown, .skipped { background: #fff8c5; }
+.unused { color: #8c959f; }
+details summary { cursor: pointer; }
+code { font-size: 0.85rem; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach(function (th) {
+  th.addEventListener("click", function () {
+    var table = th.closest("table");
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var order = th.dataset.order === "asc" ? "desc" : "asc";
+    table.querySelectorAll("th").forEach(function (other) { delete other.dataset.order; });
+    th.dataset.order = order;
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[index].dataset.sort || a.cells[index].textContent.trim();
+      var y = b.cells[index].dataset.sort || b.cells[index].textContent.trim();
+      var result = x.localeCompare(y, undefined, { numeric: true });
+      return order === "asc" ? result : -result;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+
+function applyFilters() {
+  var filters = {};
+  document.querySelectorAll("[data-filter]").forEach(function (input) {
+    filters[input.dataset.filter] = input.value.trim().toLowerCase();
+  });
+  document.querySelectorAll("tbody tr").forEach(function (row) {
+    var visible = Object.keys(filters).every(function (key) {
+      var value = row.dataset[key];
+      if (!filters[key] || value === undefined) { return true; }
+      return key === "status" ? value === filters[key] : value.toLowerCase().indexOf(filters[key]) !== -1;
+    });
+    row.hidden = !visible;
+  });
+}
+
+document.querySelectorAll("[data-filter]").forEach(function (input) {
+  input.addEventListener("input", applyFilters);
+});
+"#;
+
+/// Renders a single self-contained HTML page with inline styles and scripts.
+pub fn generate_html_report(
+    repos: &[String],
+    config: &Config,
+    issues: &TaskIssues,
+) -> Result<String> {
+    let mut tasks: Vec<_> = config
+        .get_all_tasks()
+        .into_iter()
+        .map(|SupportedTask::Default(name)| name)
+        .collect();
+    tasks.sort();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Pipeline Task Analysis Report</title>\n");
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str("<h1>📊 Pipeline Task Analysis Report</h1>\n");
+    html.push_str(&format!(
+        "<p class=\"muted\">Generated on {} by ciprobe {}</p>\n",
+        utc_timestamp(),
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    generate_summary(&mut html, issues);
+    generate_filters(&mut html);
+    generate_matrix(&mut html, repos, &tasks, config, issues);
+    generate_tasks_table(&mut html, &tasks, config, issues);
+    generate_implementations_table(&mut html, config, issues);
+    generate_failed_files_table(&mut html, issues);
+
+    html.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    Ok(html)
+}
+
+fn generate_summary(html: &mut String, issues: &TaskIssues) {
+    let cards = [
+        ("Repositories analyzed", issues.repos_analyzed.len()),
+        ("Skipped", issues.repos_skipped.len()),
+        ("Failed", GateCheck::Error.count(issues)),
+        ("Invalid versions", GateCheck::Invalid.count(issues)),
+        ("Missing required", GateCheck::Missing.count(issues)),
+        ("Forbidden usages", GateCheck::Forbidden.count(issues)),
+        ("Unknown tasks", GateCheck::Unknown.count(issues)),
+    ];
+
+    html.push_str("<div class=\"cards\">\n");
+    for (label, count) in cards {
+        html.push_str(&format!(
+            "<div class=\"card\"><strong>{}</strong>{}</div>\n",
+            count, label
+        ));
+    }
+    html.push_str("</div>\n");
+}
+
+fn generate_filters(html: &mut String) {
+    html.push_str("<div class=\"filters\">\n");
+    for (key, placeholder) in [
+        ("repo", "Filter by repository"),
+        ("task", "Filter by task"),
+        ("version", "Filter by version"),
+    ] {
+        html.push_str(&format!(
+            "<input type=\"search\" data-filter=\"{}\" placeholder=\"{}\">\n",
+            key, placeholder
+        ));
+    }
+    html.push_str("<select data-filter=\"status\">\n<option value=\"\">All statuses</option>\n");
+    for status in ["valid", "invalid", "forbidden", "unknown", "missing"] {
+        html.push_str(&format!("<option value=\"{0}\">{0}</option>\n", status));
+    }
+    html.push_str("</select>\n</div>\n");
+}
+
+fn generate_matrix(
+    html: &mut String,
+    repos: &[String],
+    tasks: &[String],
+    config: &Config,
+    issues: &TaskIssues,
+) {
+    html.push_str("<h2>Repository × Task Matrix</h2>\n<div class=\"matrix\">\n");
+    html.push_str("<table class=\"sortable\">\n<thead><tr><th>Repository</th>");
+    for task in tasks {
+        html.push_str(&format!("<th>{}</th>", escape(task)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for repo in repos {
+        let url = RepositoryUrl::parse(repo);
+        html.push_str(&format!(
+            "<tr data-repo=\"{}\"><td><a href=\"{}\">{}</a></td>",
+            escape(repo),
+            escape(&url.web_url),
+            escape(&url.display_name())
+        ));
+
+        for task in tasks {
+            let cell = if let Some(error) = issues.repos_failed.get(repo) {
+                format!(
+                    "<span class=\"status failed\" title=\"{}\">failed</span>",
+                    escape(error)
+                )
+            } else if issues.repos_skipped.contains(repo) {
+                "<span class=\"status skipped\">skipped</span>".to_string()
+            } else if issues
+                .missing_required_tasks
+                .get(repo)
+                .is_some_and(|missing| missing.contains(task))
+            {
+                "<span class=\"status missing\">missing</span>".to_string()
+            } else {
+                let versions: BTreeSet<_> = issues
+                    .all_implementations
+                    .get(task)
+                    .into_iter()
+                    .flatten()
+                    .filter(|i| &i.repo_name == repo)
+                    .map(|i| (i.version.as_str(), config.validate(task, &i.version)))
+                    .collect();
+                if versions.is_empty() {
+                    "<span class=\"unused\">–</span>".to_string()
+                } else {
+                    versions
+                        .into_iter()
+                        .map(|(version, status)| {
+                            format!(
+                                "<span class=\"status {}\" title=\"{}\">{}</span>",
+                                status,
+                                status,
+                                escape(version)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }
+            };
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n</div>\n");
+}
+
+fn generate_tasks_table(html: &mut String, tasks: &[String], config: &Config, issues: &TaskIssues) {
+    html.push_str("<h2>Tasks</h2>\n<table class=\"sortable\">\n");
+    html.push_str("<thead><tr><th>Task</th><th>Policy</th><th>Allowed versions</th><th>Status</th><th>Repositories</th><th>Usages</th></tr></thead>\n<tbody>\n");
+
+    // Configured tasks first, then the unknown ones found in pipelines
+    let mut unknown: Vec<_> = issues.missing_states.keys().cloned().collect();
+    unknown.sort();
+
+    for task in tasks.iter().chain(&unknown) {
+        let implementations: Vec<_> = issues
+            .all_implementations
+            .get(task)
+            .map(|impls| impls.iter().collect())
+            .unwrap_or_default();
+        let repo_count = implementations
+            .iter()
+            .map(|i| &i.repo_name)
+            .collect::<BTreeSet<_>>()
+            .len();
+
+        let status = task_status(task, &implementations, config, issues);
+        let policy = match config.task_versions.contains_key(task) {
+            true => config.policy(task).to_string(),
+            false => "–".to_string(),
+        };
+
+        html.push_str(&format!(
+            "<tr data-task=\"{}\" data-status=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td><span class=\"status {}\">{}</span></td><td data-sort=\"{:06}\">{}</td><td>",
+            escape(task),
+            status,
+            escape(task),
+            policy,
+            escape(&config.get_valid_versions(task).join(", ")),
+            status,
+            status,
+            repo_count,
+            repo_count
+        ));
+
+        if implementations.is_empty() {
+            html.push_str("<span class=\"unused\">none</span>");
+        } else {
+            html.push_str(&format!(
+                "<details><summary>{} usages</summary><ul>\n",
+                implementations.len()
+            ));
+            for impl_ in &implementations {
+                html.push_str(&format!(
+                    "<li>{} <code>{}:{}</code> in {}</li>\n",
+                    escape(&impl_.version),
+                    escape(&path_string(&impl_.file_path)),
+                    impl_.line,
+                    escape(&RepositoryUrl::parse(&impl_.repo_name).display_name())
+                ));
+            }
+            html.push_str("</ul></details>");
+        }
+        html.push_str("</td></tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// The worst status across a task's usages and required-task checks.
+fn task_status(
+    task: &str,
+    implementations: &[&TaskImplementation],
+    config: &Config,
+    issues: &TaskIssues,
+) -> &'static str {
+    let statuses: BTreeSet<_> = implementations
+        .iter()
+        .map(|i| config.validate(task, &i.version))
+        .collect();
+
+    if statuses.contains(&TaskStatus::Forbidden) {
+        "forbidden"
+    } else if statuses.contains(&TaskStatus::Invalid) {
+        "invalid"
+    } else if config.policy(task) == TaskPolicy::Required
+        && issues
+            .missing_required_tasks
+            .values()
+            .any(|missing| missing.iter().any(|t| t == task))
+    {
+        "missing"
+    } else if statuses.contains(&TaskStatus::Unknown) {
+        "unknown"
+    } else {
+        "valid"
+    }
+}
+
+fn generate_implementations_table(html: &mut String, config: &Config, issues: &TaskIssues) {
+    html.push_str("<h2>Implementations</h2>\n<table class=\"sortable\">\n");
+    html.push_str("<thead><tr><th>Repository</th><th>Task</th><th>Version</th><th>Status</th><th>File</th><th>Source</th></tr></thead>\n<tbody>\n");
+
+    let mut rows: Vec<_> = issues
+        .all_implementations
+        .iter()
+        .flat_map(|(task, impls)| impls.iter().map(move |i| (task, i)))
+        .collect();
+    rows.sort_by(|(t1, i1), (t2, i2)| (&i1.repo_name, t1).cmp(&(&i2.repo_name, t2)));
+
+    for (task, impl_) in rows {
+        let status = config.validate(task, &impl_.version);
+        let url = RepositoryUrl::parse(&impl_.repo_name);
+        html.push_str(&format!(
+            "<tr data-repo=\"{}\" data-task=\"{}\" data-version=\"{}\" data-status=\"{}\"><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td><span class=\"status {}\">{}</span></td><td>",
+            escape(&impl_.repo_name),
+            escape(task),
+            escape(&impl_.version),
+            status,
+            escape(&url.web_url),
+            escape(&url.display_name()),
+            escape(task),
+            escape(&impl_.version),
+            status,
+            status
+        ));
+
+        let mut file = format!(
+            "{}:{}:{}",
+            escape(&path_string(&impl_.file_path)),
+            impl_.line,
+            impl_.column
+        );
+        if let Some(link) = issues.file_url(impl_) {
+            file = format!("<a href=\"{}\">{}</a>", escape(&link), file);
+        }
+        if impl_.include_chain.len() > 1 || !impl_.consumers.is_empty() {
+            html.push_str(&format!(
+                "<details><summary><code>{}</code></summary>",
+                file
+            ));
+            if impl_.include_chain.len() > 1 {
+                html.push_str(&format!(
+                    "Reached via {}<br>",
+                    escape(&impl_.include_path())
+                ));
+            }
+            if !impl_.consumers.is_empty() {
+                html.push_str(&format!("Used by {}", escape(&impl_.consumers.join(", "))));
+            }
+            html.push_str("</details>");
+        } else {
+            html.push_str(&format!("<code>{}</code>", file));
+        }
+        html.push_str(&format!(
+            "</td><td><code>{}</code></td></tr>\n",
+            escape(&impl_.source)
+        ));
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// Pipeline files that could not be parsed, so their tasks are missing above.
+fn generate_failed_files_table(html: &mut String, issues: &TaskIssues) {
+    if issues.files_failed.is_empty() {
+        return;
+    }
+    html.push_str("<h2>Unparsed pipeline files</h2>\n<table class=\"sortable\">\n");
+    html.push_str(
+        "<thead><tr><th>Repository</th><th>File</th><th>Error</th></tr></thead>\n<tbody>\n",
+    );
+
+    let mut rows: Vec<_> = issues
+        .files_failed
+        .iter()
+        .flat_map(|(repo, files)| files.iter().map(move |(file, error)| (repo, file, error)))
+        .collect();
+    rows.sort();
+
+    for (repo, file, error) in rows {
+        let url = RepositoryUrl::parse(repo);
+        html.push_str(&format!(
+            "<tr data-repo=\"{}\" class=\"failed\"><td><a href=\"{}\">{}</a></td><td><code>{}</code></td><td>{}</td></tr>\n",
+            escape(repo),
+            escape(&url.web_url),
+            escape(&url.display_name()),
+            escape(&path_string(file)),
+            escape(error)
+        ));
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+}
+
+#[derive(Debug)]
+pub struct HtmlRenderer;
+
+impl ReportRenderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "report.html"
+    }
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String> {
+        generate_html_report(repos, config, issues)
+    }
+}
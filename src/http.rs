@@ -0,0 +1,109 @@
+use crate::error::{Error, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The response to a successful (2xx) request.
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// The value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends a GET request through `curl`, following redirects. Options are passed
+/// as a curl config on stdin so that `user` (`name:token`, sent as basic auth)
+/// and header values never show up in the process list.
+pub fn get(url: &str, user: Option<&str>, headers: &[(&str, &str)]) -> Result<HttpResponse> {
+    let mut config = format!(
+        "url = {}\nsilent\nshow-error\ninclude\nlocation\nconnect-timeout = 15\nmax-time = 120\n",
+        quote(url)
+    );
+    if let Some(user) = user {
+        config.push_str(&format!("user = {}\n", quote(user)));
+    }
+    for (name, value) in headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{}: {}", name, value))
+        ));
+    }
+
+    let mut child = Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Api(format!("Failed to run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(Error::Api(format!(
+            "GET {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let response = parse_response(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| Error::Api(format!("GET {} returned an invalid response", url)))?;
+    if !(200..300).contains(&response.status) {
+        let body: String = response.body.chars().take(200).collect();
+        return Err(Error::Api(format!(
+            "GET {} returned HTTP {}: {}",
+            url,
+            response.status,
+            body.trim()
+        )));
+    }
+    Ok(response)
+}
+
+/// Splits curl's `--include` output into the last header block and the body.
+/// Redirects and `100 Continue` responses each add a header block in front.
+fn parse_response(output: &str) -> Option<HttpResponse> {
+    let mut rest = output;
+    let mut response = None;
+
+    while rest.starts_with("HTTP/") {
+        let (head, body) = match rest.split_once("\r\n\r\n") {
+            Some(parts) => parts,
+            None => rest.split_once("\n\n").unwrap_or((rest, "")),
+        };
+        let mut lines = head.lines();
+        let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        response = Some(HttpResponse {
+            status,
+            headers,
+            body: String::new(),
+        });
+        rest = body;
+    }
+
+    response.map(|response| HttpResponse {
+        body: rest.to_string(),
+        ..response
+    })
+}
+
+/// Quotes a value for a curl config file.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,237 @@
+use crate::{
+    error::{Error, Result},
+    glob::glob_match,
+    url_provider::redact_credentials,
+    yaml_document::{parse_document, YamlNode},
+    RepositoryUrl,
+};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A repository to analyze, with the metadata an inventory file can attach.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoEntry {
+    pub url: String,
+    /// Branch, tag or commit SHA to analyze instead of the default branch.
+    pub reference: Option<String>,
+    pub team: Option<String>,
+    pub tags: Vec<String>,
+    /// A local checkout to analyze in place instead of cloning `url`.
+    pub path: Option<PathBuf>,
+}
+
+impl RepoEntry {
+    /// Creates an entry from `url`, or `url#ref` where the ref is a branch,
+    /// tag or commit SHA.
+    pub fn new(url: &str) -> Self {
+        let (url, reference) = match url.trim().split_once('#') {
+            Some((url, reference)) if !reference.trim().is_empty() => {
+                (url.trim(), Some(reference.trim().to_string()))
+            }
+            Some((url, _)) => (url.trim(), None),
+            None => (url.trim(), None),
+        };
+        // Credentials are taken from `Credentials`, never from the URL
+        Self {
+            url: redact_credentials(url),
+            reference,
+            ..Self::default()
+        }
+    }
+
+    /// Creates an entry for a local directory. Its absolute path is the
+    /// repository identity.
+    pub fn local(path: &Path) -> Result<Self> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| Error::Config(format!("Cannot analyze {}: {}", path.display(), e)))?;
+        if !path.is_dir() {
+            return Err(Error::Config(format!(
+                "Cannot analyze {}: not a directory",
+                path.display()
+            )));
+        }
+        // Windows returns verbatim paths (`\\?\C:\...`)
+        let display = path.display().to_string();
+        let path = PathBuf::from(display.trim_start_matches(r"\\?\"));
+        Ok(Self {
+            url: path.display().to_string(),
+            path: Some(path),
+            ..Self::default()
+        })
+    }
+
+    /// The identity of the repository in results and reports: the URL (or
+    /// local path), or `url#ref` when a ref is selected.
+    pub fn identity(&self) -> String {
+        match &self.reference {
+            Some(reference) => format!("{}#{}", self.url, reference),
+            None => self.url.clone(),
+        }
+    }
+}
+
+/// The repositories to analyze, from `--repos` and `--repos-file`.
+#[derive(Debug, Default)]
+pub struct Inventory {
+    pub repos: Vec<RepoEntry>,
+    /// URL or repository name patterns (`*` and `?`) that are never analyzed.
+    pub exclude: Vec<String>,
+}
+
+impl Inventory {
+    /// Parses a comma-separated list of URLs, as given to `--repos`.
+    pub fn from_list(list: &str) -> Self {
+        Self {
+            repos: list
+                .split(',')
+                .filter(|url| !url.trim().is_empty())
+                .map(RepoEntry::new)
+                .collect(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Loads a `.yml`, `.yaml` or `.json` inventory, or a plain text list
+    /// with one URL per line for any other extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!(
+                "Failed to read repository file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("yml" | "yaml" | "json") => Self::parse_manifest(&content),
+            _ => Ok(Self::parse_text(&content)),
+        }
+    }
+
+    /// Parses one URL per line. Blank lines and lines starting with `#` are
+    /// ignored, as is anything after ` #` on a line.
+    pub fn parse_text(content: &str) -> Self {
+        let repos = content
+            .lines()
+            .map(|line| line.split(" #").next().unwrap_or(line).trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(RepoEntry::new)
+            .collect();
+        Self {
+            repos,
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Parses a YAML (or JSON) inventory. Repositories are URLs or mappings
+    /// with metadata:
+    ///
+    /// ```yaml
+    /// repositories:
+    ///   - https://dev.azure.com/org/project/_git/api
+    ///   - url: https://github.com/org/web
+    ///     branch: release/2.0
+    ///   - url: https://github.com/org/cli
+    ///     tag: v1.4.0
+    ///     team: frontend
+    ///     tags: [node, public]
+    ///   - url: https://github.com/org/old
+    ///     exclude: true
+    /// exclude:
+    ///   - '*-archive'
+    /// ```
+    pub fn parse_manifest(content: &str) -> Result<Self> {
+        let document = parse_document(content)
+            .map_err(|e| Error::Config(format!("Invalid repository file: {}", e)))?;
+
+        let repositories = match document.as_sequence() {
+            Some(_) => Some(&document),
+            None => document.get("repositories"),
+        };
+        let items = repositories.and_then(|r| r.as_sequence()).ok_or_else(|| {
+            Error::Config("Invalid repository file: missing repositories list".to_string())
+        })?;
+
+        let mut inventory = Inventory::default();
+        for item in items {
+            if let Some(url) = item.as_str() {
+                inventory.repos.push(RepoEntry::new(url));
+                continue;
+            }
+
+            let url = item.get("url").and_then(|u| u.as_str()).ok_or_else(|| {
+                Error::Config(format!(
+                    "Invalid repository file: entry on line {} has no url",
+                    item.line
+                ))
+            })?;
+            let mut entry = RepoEntry::new(url);
+            // `branch`, `tag` and `commit` are all fetched as given
+            if let Some(reference) = ["branch", "tag", "commit"]
+                .iter()
+                .find_map(|key| item.get(key).and_then(|r| r.as_str()))
+            {
+                entry.reference = Some(reference.to_string());
+            }
+            entry.team = item.get("team").and_then(|t| t.as_str()).map(String::from);
+            entry.tags = string_list(item.get("tags"));
+
+            if item.get("exclude").and_then(|e| e.as_str()) == Some("true") {
+                inventory.exclude.push(entry.url);
+            } else {
+                inventory.repos.push(entry);
+            }
+        }
+        inventory
+            .exclude
+            .extend(string_list(document.get("exclude")));
+
+        Ok(inventory)
+    }
+
+    pub fn extend(&mut self, other: Inventory) {
+        self.repos.extend(other.repos);
+        self.exclude.extend(other.exclude);
+    }
+
+    /// The repositories to analyze: every entry that is not excluded, once,
+    /// in the order they were given.
+    pub fn entries(&self) -> Vec<RepoEntry> {
+        let mut seen = HashSet::new();
+        self.repos
+            .iter()
+            .filter(|entry| !self.is_excluded(entry))
+            .filter(|entry| seen.insert(entry.identity()))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether an exclusion pattern matches the URL or repository name.
+    pub fn is_excluded(&self, entry: &RepoEntry) -> bool {
+        let name = RepositoryUrl::parse(&entry.url).name;
+        self.exclude.iter().any(|pattern| {
+            let pattern = pattern.trim();
+            glob_match(pattern, &entry.url) || glob_match(pattern, &name)
+        })
+    }
+}
+
+fn string_list(node: Option<&YamlNode>) -> Vec<String> {
+    match node {
+        Some(node) => match node.as_sequence() {
+            Some(items) => items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(String::from)
+                .collect(),
+            None => node.as_str().map(String::from).into_iter().collect(),
+        },
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{
+    error::Result, gate::GateCheck, json::JsonValue, report::utc_timestamp, report::ReportRenderer,
+    Config, SupportedTask, TaskImplementation, TaskIssues,
+};
+
+/// Bumped whenever a field is renamed or removed. Adding fields keeps the version.
+pub const JSON_SCHEMA_VERSION: usize = 1;
+
+pub fn generate_json_report(
+    repos: &[String],
+    config: &Config,
+    issues: &TaskIssues,
+) -> Result<String> {
+    let report = JsonValue::object([
+        ("schema_version", JSON_SCHEMA_VERSION.into()),
+        (
+            "generator",
+            JsonValue::object([
+                ("name", "ciprobe".into()),
+                ("version", env!("CARGO_PKG_VERSION").into()),
+            ]),
+        ),
+        ("generated_at", utc_timestamp().into()),
+        ("summary", summary(issues)),
+        ("repositories", repositories(repos, issues)),
+        ("tasks", tasks(config)),
+        ("implementations", implementations(config, issues)),
+        ("invalid_states", invalid_states(config, issues)),
+        ("forbidden_usages", forbidden_usages(issues)),
+        ("missing_required_tasks", missing_required_tasks(issues)),
+        ("unknown_tasks", unknown_tasks(issues)),
+    ]);
+
+    Ok(report.to_pretty_string())
+}
+
+fn summary(issues: &TaskIssues) -> JsonValue {
+    JsonValue::object([
+        ("repos_analyzed", issues.repos_analyzed.len().into()),
+        ("repos_skipped", issues.repos_skipped.len().into()),
+        ("repos_failed", issues.repos_failed.len().into()),
+        (
+            "files_failed",
+            issues
+                .files_failed
+                .values()
+                .map(|v| v.len())
+                .sum::<usize>()
+                .into(),
+        ),
+        ("invalid", GateCheck::Invalid.count(issues).into()),
+        ("missing_required", GateCheck::Missing.count(issues).into()),
+        ("unknown", GateCheck::Unknown.count(issues).into()),
+        ("forbidden", GateCheck::Forbidden.count(issues).into()),
+    ])
+}
+
+fn repositories(repos: &[String], issues: &TaskIssues) -> JsonValue {
+    let mut failed: Vec<_> = issues
+        .repos_failed
+        .keys()
+        .chain(issues.files_failed.keys())
+        .filter(|repo| !repos.contains(repo))
+        .collect();
+    failed.sort();
+    failed.dedup();
+
+    JsonValue::array(repos.iter().chain(failed).map(|repo| {
+        let (status, error) = if let Some(error) = issues.repos_failed.get(repo) {
+            ("failed", Some(error))
+        } else if issues.repos_skipped.contains(repo) {
+            ("skipped", None)
+        } else {
+            ("analyzed", None)
+        };
+        let metadata = issues.repo_metadata.get(repo);
+        JsonValue::object([
+            ("url", repo.into()),
+            ("status", status.into()),
+            ("error", error.into()),
+            (
+                "failed_files",
+                JsonValue::array(issues.files_failed.get(repo).into_iter().flatten().map(
+                    |(file, error)| {
+                        JsonValue::object([
+                            ("file", path_string(file).into()),
+                            ("error", error.as_str().into()),
+                        ])
+                    },
+                )),
+            ),
+            ("branch", issues.repo_branches.get(repo).into()),
+            ("commit", issues.repo_commits.get(repo).into()),
+            ("team", metadata.and_then(|m| m.team.as_deref()).into()),
+            (
+                "tags",
+                JsonValue::array(metadata.into_iter().flat_map(|m| m.tags.iter())),
+            ),
+        ])
+    }))
+}
+
+fn tasks(config: &Config) -> JsonValue {
+    let mut tasks: Vec<_> = config
+        .get_all_tasks()
+        .into_iter()
+        .map(|SupportedTask::Default(name)| name)
+        .collect();
+    tasks.sort();
+
+    JsonValue::array(tasks.into_iter().map(|name| {
+        let policy = config.policy(&name).to_string();
+        let allowed_versions = JsonValue::array(config.get_valid_versions(&name));
+        JsonValue::object([
+            ("name", name.into()),
+            ("policy", policy.into()),
+            ("allowed_versions", allowed_versions),
+        ])
+    }))
+}
+
+fn implementation(
+    task: &str,
+    implementation: &TaskImplementation,
+    issues: &TaskIssues,
+) -> Vec<(&'static str, JsonValue)> {
+    vec![
+        ("task", task.into()),
+        ("repo", implementation.repo_name.as_str().into()),
+        (
+            "commit",
+            issues.repo_commits.get(&implementation.repo_name).into(),
+        ),
+        ("file", path_string(&implementation.file_path).into()),
+        ("line", implementation.line.into()),
+        ("column", implementation.column.into()),
+        ("url", issues.file_url(implementation).into()),
+        ("source", implementation.source.as_str().into()),
+        ("version", implementation.version.as_str().into()),
+        (
+            "version_kind",
+            implementation.version_kind().to_string().into(),
+        ),
+        (
+            "include_chain",
+            JsonValue::array(implementation.include_chain.iter().map(|p| path_string(p))),
+        ),
+        (
+            "consumers",
+            JsonValue::array(implementation.consumers.iter()),
+        ),
+    ]
+}
+
+fn implementations(config: &Config, issues: &TaskIssues) -> JsonValue {
+    let mut entries: Vec<_> = issues
+        .all_implementations
+        .iter()
+        .flat_map(|(task, impls)| impls.iter().map(move |i| (task, i)))
+        .collect();
+    entries.sort();
+
+    JsonValue::array(entries.into_iter().map(|(task, impl_)| {
+        let mut fields = implementation(task, impl_, issues);
+        fields.insert(
+            1,
+            (
+                "status",
+                config.validate(task, &impl_.version).to_string().into(),
+            ),
+        );
+        JsonValue::object(fields)
+    }))
+}
+
+fn invalid_states(config: &Config, issues: &TaskIssues) -> JsonValue {
+    let mut entries: Vec<_> = issues
+        .invalid_states
+        .iter()
+        .flat_map(|(task, repos)| repos.values().flatten().map(move |i| (task, i)))
+        .collect();
+    entries.sort();
+
+    JsonValue::array(entries.into_iter().map(|(task, impl_)| {
+        let mut fields = implementation(task, impl_, issues);
+        fields.insert(
+            1,
+            (
+                "allowed_versions",
+                JsonValue::array(config.get_valid_versions(task)),
+            ),
+        );
+        JsonValue::object(fields)
+    }))
+}
+
+fn forbidden_usages(issues: &TaskIssues) -> JsonValue {
+    let mut entries: Vec<_> = issues
+        .forbidden_usages
+        .iter()
+        .flat_map(|(task, impls)| impls.iter().map(move |i| (task, i)))
+        .collect();
+    entries.sort();
+
+    JsonValue::array(
+        entries
+            .into_iter()
+            .map(|(task, impl_)| JsonValue::object(implementation(task, impl_, issues))),
+    )
+}
+
+fn missing_required_tasks(issues: &TaskIssues) -> JsonValue {
+    let mut repos: Vec<_> = issues.missing_required_tasks.iter().collect();
+    repos.sort();
+
+    JsonValue::array(repos.into_iter().map(|(repo, tasks)| {
+        let mut tasks = tasks.clone();
+        tasks.sort();
+        JsonValue::object([("repo", repo.into()), ("tasks", JsonValue::array(tasks))])
+    }))
+}
+
+fn unknown_tasks(issues: &TaskIssues) -> JsonValue {
+    let mut names: Vec<_> = issues.missing_states.values().collect();
+    names.sort();
+    JsonValue::array(names)
+}
+
+// Forward slashes on every platform so reports compare across machines
+pub(crate) fn path_string(path: &std::path::Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[derive(Debug)]
+pub struct JsonRenderer;
+
+impl ReportRenderer for JsonRenderer {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "report.json"
+    }
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String> {
+        generate_json_report(repos, config, issues)
+    }
+}
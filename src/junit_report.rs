@@ -0,0 +1,214 @@
+use crate::{
+    error::Result,
+    json_report::path_string,
+    report::ReportRenderer,
+    report::{escape_xml as escape, utc_timestamp},
+    Config, SupportedTask, TaskIssues, TaskPolicy, TaskStatus,
+};
+
+enum Outcome {
+    Passed,
+    Failed { message: String, details: String },
+    Skipped(String),
+    Error(String),
+}
+
+struct TestCase {
+    name: String,
+    outcome: Outcome,
+}
+
+/// Renders a JUnit XML report: one test suite per repository and one test
+/// case per configured task.
+pub fn generate_junit_report(
+    repos: &[String],
+    config: &Config,
+    issues: &TaskIssues,
+) -> Result<String> {
+    let mut tasks: Vec<_> = config
+        .get_all_tasks()
+        .into_iter()
+        .map(|SupportedTask::Default(name)| name)
+        .collect();
+    tasks.sort();
+
+    // Shared template repositories are reported as their own suites
+    let mut template_repos: Vec<_> = issues
+        .all_implementations
+        .values()
+        .flatten()
+        .map(|i| &i.repo_name)
+        .chain(issues.files_failed.keys())
+        .filter(|repo| !repos.contains(repo))
+        .collect();
+    template_repos.sort();
+    template_repos.dedup();
+
+    let suites: Vec<_> = repos
+        .iter()
+        .chain(template_repos)
+        .map(|repo| {
+            let mut cases: Vec<_> = tasks
+                .iter()
+                .map(|task| TestCase {
+                    name: task.clone(),
+                    outcome: outcome(repo, task, config, issues),
+                })
+                .collect();
+            // Tasks of unparsed files are unknown, so each file is an error
+            let mut failed_files: Vec<_> = issues
+                .files_failed
+                .get(repo)
+                .into_iter()
+                .flatten()
+                .collect();
+            failed_files.sort();
+            cases.extend(failed_files.into_iter().map(|(file, error)| TestCase {
+                name: path_string(file),
+                outcome: Outcome::Error(error.clone()),
+            }));
+            (repo.as_str(), cases)
+        })
+        .collect();
+
+    let count = |matches: fn(&Outcome) -> bool| {
+        suites
+            .iter()
+            .flat_map(|(_, cases)| cases)
+            .filter(|case| matches(&case.outcome))
+            .count()
+    };
+
+    let timestamp = utc_timestamp();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"ciprobe\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"0\">\n",
+        count(|_| true),
+        count(|o| matches!(o, Outcome::Failed { .. })),
+        count(|o| matches!(o, Outcome::Error(_))),
+        count(|o| matches!(o, Outcome::Skipped(_))),
+    ));
+
+    for (repo, cases) in &suites {
+        let suite_count = |matches: fn(&Outcome) -> bool| {
+            cases.iter().filter(|case| matches(&case.outcome)).count()
+        };
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"0\" timestamp=\"{}\">\n",
+            escape(repo),
+            cases.len(),
+            suite_count(|o| matches!(o, Outcome::Failed { .. })),
+            suite_count(|o| matches!(o, Outcome::Error(_))),
+            suite_count(|o| matches!(o, Outcome::Skipped(_))),
+            timestamp
+        ));
+
+        for case in cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"0\"",
+                escape(&case.name),
+                escape(repo)
+            );
+            match &case.outcome {
+                Outcome::Passed => xml.push_str(&format!("{}/>\n", open)),
+                Outcome::Failed { message, details } => xml.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\" type=\"ciprobe.violation\">{}</failure>\n    </testcase>\n",
+                    open,
+                    escape(message),
+                    escape(details)
+                )),
+                Outcome::Skipped(message) => xml.push_str(&format!(
+                    "{}>\n      <skipped message=\"{}\"/>\n    </testcase>\n",
+                    open,
+                    escape(message)
+                )),
+                Outcome::Error(message) => xml.push_str(&format!(
+                    "{}>\n      <error message=\"{}\" type=\"ciprobe.error\"/>\n    </testcase>\n",
+                    open,
+                    escape(message)
+                )),
+            }
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    Ok(xml)
+}
+
+fn outcome(repo: &str, task: &str, config: &Config, issues: &TaskIssues) -> Outcome {
+    if let Some(error) = issues.repos_failed.get(repo) {
+        return Outcome::Error(error.clone());
+    }
+    if issues.repos_skipped.contains(repo) {
+        return Outcome::Skipped("No pipeline files found".to_string());
+    }
+
+    let implementations: Vec<_> = issues
+        .all_implementations
+        .get(task)
+        .into_iter()
+        .flatten()
+        .filter(|i| i.repo_name == repo)
+        .collect();
+
+    let violations: Vec<_> = implementations
+        .iter()
+        .filter(|i| config.validate(task, &i.version) != TaskStatus::Valid)
+        .map(|i| {
+            format!(
+                "Found version {} in {}:{}:{}",
+                i.version,
+                path_string(&i.file_path),
+                i.line,
+                i.column
+            )
+        })
+        .collect();
+
+    if !violations.is_empty() {
+        let message = match config.policy(task) {
+            TaskPolicy::Forbidden => format!("Task '{}' is forbidden", task),
+            _ => format!(
+                "Task '{}' has an invalid version (expected {})",
+                task,
+                config.get_valid_versions(task).join(" or ")
+            ),
+        };
+        return Outcome::Failed {
+            message,
+            details: violations.join("\n"),
+        };
+    }
+
+    let missing = issues
+        .missing_required_tasks
+        .get(repo)
+        .is_some_and(|tasks| tasks.iter().any(|t| t == task));
+    if missing {
+        return Outcome::Failed {
+            message: format!("Required task '{}' is missing", task),
+            details: format!("No pipeline file in {} uses '{}'", repo, task),
+        };
+    }
+
+    Outcome::Passed
+}
+
+#[derive(Debug)]
+pub struct JunitRenderer;
+
+impl ReportRenderer for JunitRenderer {
+    fn name(&self) -> &'static str {
+        "junit"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "report.xml"
+    }
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String> {
+        generate_junit_report(repos, config, issues)
+    }
+}
@@ -1,26 +1,52 @@
 pub mod analyzer;
+pub mod azure_pipeline;
 pub mod cli;
 pub mod cli_handler;
 pub mod collector;
 pub mod config;
+pub mod console;
+pub mod discovery;
 pub mod error;
+pub mod gate;
 pub mod git_manager;
+pub mod github_actions;
+pub mod gitlab_ci;
+pub mod glob;
+pub mod html_report;
+pub mod http;
+pub mod inventory;
+pub mod json;
+pub mod json_report;
+pub mod junit_report;
 pub mod line_parser;
 pub mod pipeline_detector;
 pub mod report;
+pub mod sarif_report;
 pub mod task_issues;
 pub mod task_types;
+pub mod template_resolver;
+pub mod url_provider;
+pub mod yaml_document;
 pub mod yaml_parser;
 
-pub use analyzer::analyze_pipelines;
+pub use analyzer::{analyze_pipelines, expand_branches, AnalysisOptions};
+pub use azure_pipeline::{AzurePipeline, PipelineStep};
 pub use cli::Cli;
 pub use cli_handler::handle_cli;
 pub use collector::{CollectedTask, TaskImplementationCollector};
-pub use config::{Config, Credentials, VersionCompare};
+pub use config::{Config, Credentials, TaskPolicy, VersionCompare, VersionRequirement};
+pub use discovery::{AzureDevOpsDiscovery, GitHubDiscovery, RepoFilter};
 pub use error::{Error, Result};
+pub use gate::{Gate, GateCheck, GateViolation};
 pub use git_manager::GitManager;
-pub use line_parser::parse_task_definition;
-pub use pipeline_detector::find_pipeline_files;
+pub use github_actions::GitHubWorkflow;
+pub use gitlab_ci::GitLabPipeline;
+pub use inventory::{Inventory, RepoEntry};
+pub use line_parser::{parse_action_reference, parse_task_definition};
+pub use pipeline_detector::{detect_platform, find_pipeline_files, Platform};
 pub use task_issues::TaskIssues;
-pub use task_types::{SupportedTask, TaskImplementation};
+pub use task_types::{SupportedTask, TaskImplementation, TaskStatus, VersionKind};
+pub use template_resolver::TemplateResolver;
+pub use url_provider::{redact_credentials, Provider, RepositoryUrl};
+pub use yaml_document::{YamlNode, YamlValue};
 pub use yaml_parser::YamlConfig;
@@ -1,9 +1,17 @@
+use crate::task_types::VersionKind;
+
 #[derive(Debug, PartialEq)]
 pub struct TaskDefinition {
     pub name: String,
     pub version: String,
 }
 
+impl TaskDefinition {
+    pub fn version_kind(&self) -> VersionKind {
+        VersionKind::classify(&self.version)
+    }
+}
+
 pub fn parse_task_definition(line: &str) -> Option<TaskDefinition> {
     // Trim whitespace and skip if empty
     let line = line.trim();
@@ -18,14 +26,19 @@ pub fn parse_task_definition(line: &str) -> Option<TaskDefinition> {
 
     // Find "task:" anywhere in the line and get everything after "task:"
     let task_pos = line.find("task:")?;
-    let after_task = line[task_pos + 5..].trim();
+    parse_task_reference(&line[task_pos + 5..])
+}
+
+/// Parses the value of a `task:` key, e.g. `UseNode@1`.
+pub fn parse_task_reference(reference: &str) -> Option<TaskDefinition> {
+    let reference = reference.trim();
 
     // Find the @ symbol
-    let at_pos = after_task.find('@')?;
+    let at_pos = reference.find('@')?;
 
     // Split into name and version
-    let name = after_task[..at_pos].trim();
-    let version = after_task[at_pos + 1..].trim();
+    let name = reference[..at_pos].trim();
+    let version = reference[at_pos + 1..].trim();
 
     // Validate name (word chars and forward slashes only)
     if !name
@@ -36,8 +49,39 @@ pub fn parse_task_definition(line: &str) -> Option<TaskDefinition> {
         return None;
     }
 
-    // Validate version (digits only)
-    if !version.chars().all(|c| c.is_ascii_digit()) || version.is_empty() {
+    // Validate version (number, semver, branch or commit SHA)
+    if !is_valid_ref(version) {
+        return None;
+    }
+
+    Some(TaskDefinition {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// Parses the value of a GitHub Actions `uses:` key, e.g. `actions/checkout@v4`
+/// or `org/repo/.github/workflows/build.yml@main`. Local (`./path`) and
+/// `docker://` references have no version and are not returned.
+pub fn parse_action_reference(reference: &str) -> Option<TaskDefinition> {
+    let reference = reference.trim();
+    if reference.starts_with("./") || reference.starts_with("docker://") {
+        return None;
+    }
+
+    let (name, version) = reference.rsplit_once('@')?;
+
+    // Validate name (owner/repo with an optional path)
+    if !name.contains('/')
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+    {
+        return None;
+    }
+
+    // Validate ref (tag, branch or commit SHA)
+    if !is_valid_ref(version) {
         return None;
     }
 
@@ -46,3 +90,58 @@ pub fn parse_task_definition(line: &str) -> Option<TaskDefinition> {
         version: version.to_string(),
     })
 }
+
+/// Parses a container image reference such as `node:18-alpine` or
+/// `registry.example.com/team/app@sha256:...`. The image path is the name and
+/// the tag (or digest) the version; untagged images are `latest`.
+pub fn parse_image_reference(reference: &str) -> Option<TaskDefinition> {
+    let reference = reference.trim();
+    if reference.is_empty() || reference.contains('$') || reference.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let (name, version) = if let Some((name, digest)) = reference.split_once('@') {
+        (name, digest)
+    } else {
+        let last_segment = reference.rfind('/').map(|i| i + 1).unwrap_or(0);
+        match reference[last_segment..].rfind(':') {
+            Some(colon) => (
+                &reference[..last_segment + colon],
+                &reference[last_segment + colon + 1..],
+            ),
+            None => (reference, "latest"),
+        }
+    };
+
+    if name.is_empty() || version.is_empty() {
+        return None;
+    }
+
+    Some(TaskDefinition {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// Parses a GitLab CI/CD component reference such as
+/// `$CI_SERVER_FQDN/group/project/component@1.0`. The server host is dropped
+/// so the name stays the same across GitLab instances.
+pub fn parse_component_reference(reference: &str) -> Option<TaskDefinition> {
+    let (path, version) = reference.trim().rsplit_once('@')?;
+    let (host, name) = path.split_once('/')?;
+    if host.is_empty() || name.is_empty() || version.is_empty() || name.contains('$') {
+        return None;
+    }
+
+    Some(TaskDefinition {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+fn is_valid_ref(version: &str) -> bool {
+    !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+}
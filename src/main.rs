@@ -1,6 +1,13 @@
-use ciprobe::{cli::Cli, cli_handler::handle_cli, error::Result};
+use ciprobe::{cli::Cli, cli_handler::handle_cli, gate::error_exit_code};
 
-fn main() -> Result<()> {
-    let cli = Cli::parse()?;
-    handle_cli(&cli)
+fn main() {
+    let exit_code = match Cli::parse().and_then(|cli| handle_cli(&cli)) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            error_exit_code(&e)
+        }
+    };
+
+    std::process::exit(exit_code);
 }
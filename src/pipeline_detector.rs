@@ -1,13 +1,58 @@
+use crate::console;
 use crate::error::Result;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-pub fn find_pipeline_files(repo_path: &PathBuf, verbose: bool) -> Result<Vec<PathBuf>> {
+/// Hidden directories that hold CI configuration and are searched anyway.
+const CI_DIRECTORIES: [&str; 4] = [".github", ".gitlab", ".azuredevops", ".pipelines"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    AzurePipelines,
+    GitHubActions,
+    GitLabCi,
+}
+
+/// Decides which CI system a pipeline file belongs to from its repo-relative path.
+pub fn detect_platform(relative_path: &Path) -> Platform {
+    let file_name = relative_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("");
+    let in_workflows_dir = relative_path
+        .parent()
+        .map(|dir| dir.ends_with(Path::new(".github").join("workflows")))
+        .unwrap_or(false);
+
+    if in_workflows_dir || file_name == "action.yml" || file_name == "action.yaml" {
+        Platform::GitHubActions
+    } else if file_name == ".gitlab-ci.yml"
+        || file_name == ".gitlab-ci.yaml"
+        || relative_path.starts_with(".gitlab")
+    {
+        Platform::GitLabCi
+    } else {
+        Platform::AzurePipelines
+    }
+}
+
+pub fn find_pipeline_files(repo_path: &Path, verbose: bool) -> Result<Vec<PathBuf>> {
     if verbose {
-        println!("Searching for pipeline files in {:?}", repo_path);
+        console!("Searching for pipeline files in {:?}", repo_path);
     }
 
-    let mut pipeline_files = Vec::new();
-    find_yaml_files_recursive(repo_path, &mut pipeline_files)?;
+    let mut pipeline_files = match git_listed_files(repo_path) {
+        Some(files) => files,
+        None => {
+            let mut files = Vec::new();
+            find_yaml_files_recursive(repo_path, &mut files)?;
+            files
+        }
+    };
+    pipeline_files.sort();
 
     if verbose {
         for path in &pipeline_files {
@@ -18,24 +63,62 @@ pub fn find_pipeline_files(repo_path: &PathBuf, verbose: bool) -> Result<Vec<Pat
             let final_path = stripped_path
                 .strip_prefix(repo_name)
                 .unwrap_or(stripped_path);
-            println!("   └─ Found: {}", final_path.display());
+            console!("   └─ Found: {}", final_path.display());
         }
     }
 
     Ok(pipeline_files)
 }
 
-fn find_yaml_files_recursive(dir: &PathBuf, files: &mut Vec<PathBuf>) -> Result<()> {
+fn is_yaml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml")
+}
+
+/// The YAML files git knows of in a work tree: tracked, or untracked and not
+/// ignored. Ignored trees such as `node_modules` and nested repositories such
+/// as the `temp_repos` checkouts are left out. `None` outside a work tree.
+fn git_listed_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let mut files: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|file| !file.is_empty())
+        .map(|file| dir.join(file))
+        // Deleted files are still listed until the deletion is staged
+        .filter(|path| is_yaml(path) && path.is_file())
+        .collect();
+    files.dedup();
+    Some(files)
+}
+
+/// Walks a directory that is not a git work tree, skipping hidden and
+/// dependency directories and nested repositories.
+fn find_yaml_files_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
 
         if path.is_dir() {
-            find_yaml_files_recursive(&path, files)?;
-        } else if let Some(ext) = path.extension() {
-            if ext == "yml" || ext == "yaml" {
-                files.push(path);
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let hidden = name.starts_with('.') && !CI_DIRECTORIES.contains(&name.as_ref());
+            if !hidden && name != "node_modules" && !path.join(".git").exists() {
+                find_yaml_files_recursive(&path, files)?;
             }
+        } else if is_yaml(&path) {
+            files.push(path);
         }
     }
     Ok(())
@@ -1,7 +1,111 @@
-use crate::{error::Result, Config, SupportedTask, TaskIssues};
-use std::collections::HashSet;
+use crate::{
+    error::{Error, Result},
+    html_report::HtmlRenderer,
+    json_report::JsonRenderer,
+    junit_report::JunitRenderer,
+    sarif_report::SarifRenderer,
+    Config, RepositoryUrl, SupportedTask, TaskImplementation, TaskIssues, VersionCompare,
+    VersionKind,
+};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Renders an analysis result in one output format. New formats only need an
+/// implementation and an entry in `RENDERERS`.
+pub trait ReportRenderer: std::fmt::Debug + Sync {
+    /// Name used with `--format` and `--report`.
+    fn name(&self) -> &'static str;
+
+    /// Other accepted names, e.g. `md` for markdown.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn default_output(&self) -> &'static str;
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String>;
+}
+
+pub const RENDERERS: &[&dyn ReportRenderer] = &[
+    &MarkdownRenderer,
+    &JsonRenderer,
+    &SarifRenderer,
+    &JunitRenderer,
+    &HtmlRenderer,
+];
+
+pub fn find_renderer(name: &str) -> Result<&'static dyn ReportRenderer> {
+    let name = name.trim().to_lowercase();
+    RENDERERS
+        .iter()
+        .copied()
+        .find(|r| r.name() == name || r.aliases().contains(&name.as_str()))
+        .ok_or_else(|| {
+            let names: Vec<_> = RENDERERS.iter().map(|r| r.name()).collect();
+            Error::Cli(format!(
+                "Unknown report format '{}'. Expected one of: {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+/// A report to write: a format and the file it goes to.
+#[derive(Debug, Clone)]
+pub struct ReportOutput {
+    pub renderer: &'static dyn ReportRenderer,
+    pub path: PathBuf,
+}
+
+impl ReportOutput {
+    pub fn new(renderer: &'static dyn ReportRenderer, path: Option<PathBuf>) -> Self {
+        let path = path.unwrap_or_else(|| PathBuf::from(renderer.default_output()));
+        Self { renderer, path }
+    }
+
+    /// Parses `<format>=<path>`, or `<format>` for its default file name.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (format, path) = match spec.split_once('=') {
+            Some((format, path)) if !path.trim().is_empty() => {
+                (format, Some(PathBuf::from(path.trim())))
+            }
+            Some((format, _)) => (format, None),
+            None => (spec, None),
+        };
+        Ok(Self::new(find_renderer(format)?, path))
+    }
+
+    pub fn write(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<()> {
+        let report = self.renderer.render(repos, config, issues)?;
+        fs::write(&self.path, report)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MarkdownRenderer;
+
+impl ReportRenderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["md"]
+    }
+
+    fn default_output(&self) -> &'static str {
+        "report.md"
+    }
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String> {
+        generate_markdown_report(repos, config, issues)
+    }
+}
+
 pub fn generate_markdown_report(
     repos: &[String],
     config: &Config,
@@ -11,12 +115,28 @@ pub fn generate_markdown_report(
 
     generate_header(&mut md, repos, issues);
     generate_summary_section(&mut md, issues, config)?;
+    if issues
+        .repo_metadata
+        .values()
+        .any(|entry| entry.team.is_some())
+    {
+        generate_teams_section(&mut md, issues);
+    }
+    if !issues.branch_groups().is_empty() {
+        generate_branch_drift_section(&mut md, issues);
+    }
     if !issues.all_implementations.is_empty() {
         generate_valid_states_section(&mut md, issues, config)?;
     }
     if !issues.invalid_states.is_empty() {
         generate_invalid_states_section(&mut md, issues, config)?;
     }
+    if !issues.forbidden_usages.is_empty() {
+        generate_forbidden_section(&mut md, issues)?;
+    }
+    if !issues.missing_required_tasks.is_empty() {
+        generate_missing_required_section(&mut md, issues)?;
+    }
     if !issues.missing_states.is_empty() {
         generate_missing_states_section(&mut md, issues)?;
     }
@@ -24,33 +144,86 @@ pub fn generate_markdown_report(
     Ok(md)
 }
 
+/// The current time as an ISO 8601 UTC timestamp, e.g. `2024-05-01T12:30:00Z`.
+pub fn utc_timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format_utc_timestamp(secs)
+}
+
+pub fn format_utc_timestamp(secs: u64) -> String {
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let days = (secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        (secs % 86400) / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Escapes text for XML and HTML element content and attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The abbreviated commit SHA shown in reports.
+fn short_sha(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
+}
+
 fn generate_header(md: &mut String, repos: &[String], issues: &TaskIssues) {
     md.push_str("# 📊 Pipeline Task Analysis Report\n\n");
 
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let secs = now.as_secs();
-    let datetime = format!(
-        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
-        1970 + (secs / 31536000),          // years since 1970
-        ((secs % 31536000) / 2592000) + 1, // months
-        ((secs % 2592000) / 86400) + 1,    // days
-        (secs % 86400) / 3600,             // hours
-        (secs % 3600) / 60,                // minutes
-        secs % 60                          // seconds
-    );
-    md.push_str(&format!("🕒 Generated on: {}\n\n", datetime));
+    md.push_str(&format!("🕒 Generated on: {}\n\n", utc_timestamp()));
 
     // Analyzed repositories
     md.push_str("## 📚 Analyzed Repositories\n\n");
     for repo in repos {
-        if !issues.repos_skipped.contains(repo) {
-            let short_name = repo
-                .split('/')
-                .last()
-                .unwrap_or(repo)
-                .trim_end_matches("/_git/")
-                .trim_end_matches(".git");
-            md.push_str(&format!("- [{}]({})\n", short_name, repo));
+        if !issues.repos_skipped.contains(repo) && !issues.repos_failed.contains_key(repo) {
+            let url = RepositoryUrl::parse(repo);
+            md.push_str(&format!("- [{}]({})", url.display_name(), url.web_url));
+            let branch = issues.repo_branches.get(repo);
+            match (branch, issues.repo_commits.get(repo)) {
+                (Some(branch), Some(commit)) if branch != commit => {
+                    md.push_str(&format!(" (`{}` @ `{}`)", branch, short_sha(commit)))
+                }
+                (_, Some(commit)) => md.push_str(&format!(" (`{}`)", short_sha(commit))),
+                (Some(branch), None) => md.push_str(&format!(" (`{}`)", branch)),
+                (None, None) => {}
+            }
+            md.push('\n');
         }
     }
     md.push('\n');
@@ -59,19 +232,41 @@ fn generate_header(md: &mut String, repos: &[String], issues: &TaskIssues) {
     if !issues.repos_skipped.is_empty() {
         md.push_str("## ⏭️ Skipped Repositories\n\n");
         for repo in &issues.repos_skipped {
-            let short_name = repo
-                .split('/')
-                .last()
-                .unwrap_or(repo)
-                .trim_end_matches("/_git/")
-                .trim_end_matches(".git");
+            let url = RepositoryUrl::parse(repo);
             md.push_str(&format!(
                 "- [{}]({}) (no pipeline files found)\n",
-                short_name, repo
+                url.display_name(),
+                url.web_url
             ));
         }
         md.push('\n');
     }
+
+    // Repositories that could not be analyzed
+    if !issues.repos_failed.is_empty() {
+        md.push_str("## 💥 Failed Repositories\n\n");
+        let mut failed: Vec<_> = issues.repos_failed.iter().collect();
+        failed.sort();
+        for (repo, error) in failed {
+            md.push_str(&format!("- {}: {}\n", repo, error));
+        }
+        md.push('\n');
+    }
+
+    // Pipeline files whose tasks could not be checked
+    if !issues.files_failed.is_empty() {
+        md.push_str("## 📄 Unparsed Pipeline Files\n\n");
+        let mut failed: Vec<_> = issues
+            .files_failed
+            .iter()
+            .flat_map(|(repo, files)| files.iter().map(move |(file, error)| (repo, file, error)))
+            .collect();
+        failed.sort();
+        for (repo, file, error) in failed {
+            md.push_str(&format!("- {}: `{}`: {}\n", repo, file.display(), error));
+        }
+        md.push('\n');
+    }
 }
 
 fn generate_summary_section(md: &mut String, issues: &TaskIssues, _config: &Config) -> Result<()> {
@@ -82,6 +277,12 @@ fn generate_summary_section(md: &mut String, issues: &TaskIssues, _config: &Conf
         .missing_required_tasks
         .keys()
         .chain(issues.invalid_states.values().flat_map(|m| m.keys()))
+        .chain(
+            issues
+                .forbidden_usages
+                .values()
+                .flat_map(|impls| impls.iter().map(|i| &i.repo_name)),
+        )
         .chain(
             issues
                 .all_implementations
@@ -112,12 +313,24 @@ fn generate_summary_section(md: &mut String, issues: &TaskIssues, _config: &Conf
             issues.repos_skipped.len()
         ));
     }
+    if !issues.repos_failed.is_empty() {
+        md.push_str(&format!(
+            "- 💥 Failed repositories: {}\n",
+            issues.repos_failed.len()
+        ));
+    }
+    if !issues.files_failed.is_empty() {
+        md.push_str(&format!(
+            "- 📄 Unparsed pipeline files: {}\n",
+            issues.files_failed.values().map(|v| v.len()).sum::<usize>()
+        ));
+    }
     md.push_str(&format!(
         "- ⚠️ Repositories with issues: {}\n",
         repos_with_issues.len()
     ));
     md.push_str(&format!(
-        "- ❌ Total missing task implementations: {}\n",
+        "- ❌ Total missing required tasks: {}\n",
         issues
             .missing_required_tasks
             .values()
@@ -133,11 +346,128 @@ fn generate_summary_section(md: &mut String, issues: &TaskIssues, _config: &Conf
             .map(|v| v.len())
             .sum::<usize>()
     ));
+    if !issues.forbidden_usages.is_empty() {
+        md.push_str(&format!(
+            "- 🚫 Total forbidden task usages: {}\n",
+            issues
+                .forbidden_usages
+                .values()
+                .map(|v| v.len())
+                .sum::<usize>()
+        ));
+    }
 
     md.push('\n');
     Ok(())
 }
 
+/// Findings per owning team, for repositories listed in an inventory file.
+fn generate_teams_section(md: &mut String, issues: &TaskIssues) {
+    md.push_str("## 👥 Teams\n\n");
+    md.push_str("| Team | Repositories | Invalid | Missing | Forbidden |\n");
+    md.push_str("|------|--------------|---------|---------|-----------|\n");
+
+    let mut teams: BTreeMap<&str, [usize; 4]> = BTreeMap::new();
+    for repo in issues.repo_metadata.keys() {
+        let counts = teams.entry(issues.team(repo).unwrap_or("-")).or_default();
+        counts[0] += 1;
+        counts[1] += issues
+            .invalid_states
+            .values()
+            .filter_map(|repos| repos.get(repo))
+            .map(Vec::len)
+            .sum::<usize>();
+        counts[2] += issues.missing_required_tasks.get(repo).map_or(0, Vec::len);
+        counts[3] += issues
+            .forbidden_usages
+            .values()
+            .flatten()
+            .filter(|impl_| &impl_.repo_name == repo)
+            .count();
+    }
+
+    for (team, [repos, invalid, missing, forbidden]) in teams {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            team, repos, invalid, missing, forbidden
+        ));
+    }
+    md.push('\n');
+}
+
+/// A branch × task version matrix per repository analyzed on several
+/// branches. Versions older than the newest one on any branch are flagged.
+fn generate_branch_drift_section(md: &mut String, issues: &TaskIssues) {
+    md.push_str("## 🌿 Branch Drift\n\n");
+
+    for (url, repos) in issues.branch_groups() {
+        let repo_url = RepositoryUrl::parse(url);
+        md.push_str(&format!(
+            "### [{}]({})\n\n",
+            repo_url.display_name(),
+            repo_url.web_url
+        ));
+
+        let versions: Vec<_> = repos
+            .iter()
+            .map(|repo| issues.task_versions(repo))
+            .collect();
+        let tasks: BTreeSet<&str> = versions.iter().flat_map(|v| v.keys().copied()).collect();
+        let branches: Vec<&str> = repos
+            .iter()
+            .map(|repo| {
+                issues
+                    .repo_branches
+                    .get(*repo)
+                    .map(String::as_str)
+                    .or_else(|| repo.split_once('#').map(|(_, reference)| reference))
+                    .unwrap_or(repo)
+            })
+            .collect();
+
+        md.push_str(&format!("| Task | {} |\n", branches.join(" | ")));
+        md.push_str(&format!("|------|{}\n", "---|".repeat(branches.len())));
+
+        let mut outdated = BTreeSet::new();
+        for task in &tasks {
+            // The newest version of the task on any branch
+            let newest = versions
+                .iter()
+                .filter_map(|v| v.get(task))
+                .flatten()
+                .copied()
+                .max_by(|a, b| a.version_cmp(b).unwrap_or(Ordering::Equal));
+
+            let cells: Vec<String> = versions
+                .iter()
+                .zip(&branches)
+                .map(|(v, branch)| match v.get(task) {
+                    Some(found) => {
+                        let behind = found.iter().all(|version| {
+                            newest.and_then(|n| version.version_cmp(n)) == Some(Ordering::Less)
+                        });
+                        let found = found.iter().copied().collect::<Vec<_>>().join(", ");
+                        if behind {
+                            outdated.insert(*branch);
+                            format!("⚠️ {}", found)
+                        } else {
+                            found
+                        }
+                    }
+                    None => "-".to_string(),
+                })
+                .collect();
+            md.push_str(&format!("| {} | {} |\n", task, cells.join(" | ")));
+        }
+        md.push('\n');
+
+        if !outdated.is_empty() {
+            let outdated: Vec<_> = outdated.iter().map(|b| format!("`{}`", b)).collect();
+            md.push_str(&format!("Outdated branches: {}\n\n", outdated.join(", ")));
+        }
+    }
+}
+
 fn generate_invalid_states_section(
     md: &mut String,
     issues: &TaskIssues,
@@ -168,35 +498,33 @@ fn generate_invalid_states_section(
         ));
 
         for (repo_url, implementations) in repos {
-            let short_name = repo_url
-                .split('/')
-                .last()
-                .unwrap_or(repo_url)
-                .trim_end_matches("/_git/")
-                .trim_end_matches(".git");
-            md.push_str(&format!("#### 📁 [{}]({})\n\n", short_name, repo_url));
+            let url = RepositoryUrl::parse(repo_url);
+            md.push_str(&format!(
+                "#### 📁 [{}]({})\n\n",
+                url.display_name(),
+                url.web_url
+            ));
 
             for impl_ in implementations {
-                let path = impl_
-                    .file_path
-                    .strip_prefix(std::path::Path::new("C:\\repos\\ciprobe\\temp_repos"))
-                    .unwrap_or(&impl_.file_path);
-
-                let path = if let Some(repo_name) = repo_url
-                    .split('/')
-                    .last()
-                    .map(|s| s.trim_end_matches("/_git/").trim_end_matches(".git"))
-                {
-                    path.strip_prefix(repo_name).unwrap_or(path)
-                } else {
-                    path
+                let version = match impl_.version_kind() {
+                    kind @ (VersionKind::Branch | VersionKind::Commit) => {
+                        format!("{} ({})", impl_.version, kind)
+                    }
+                    _ => impl_.version.clone(),
                 };
-
                 md.push_str(&format!(
-                    "- Version {} in `{}`\n",
-                    impl_.version,
-                    path.display()
+                    "- Version {} in {}",
+                    version,
+                    markdown_location(impl_, issues)
                 ));
+                if impl_.include_chain.len() > 1 {
+                    md.push_str(&format!(" (reached via {})", impl_.include_path()));
+                }
+                if !impl_.consumers.is_empty() {
+                    md.push_str(&format!(" — used by {}", impl_.consumers.join(", ")));
+                }
+                md.push_str(&markdown_source(impl_));
+                md.push('\n');
             }
             md.push('\n');
         }
@@ -205,6 +533,67 @@ fn generate_invalid_states_section(
     Ok(())
 }
 
+fn generate_forbidden_section(md: &mut String, issues: &TaskIssues) -> Result<()> {
+    md.push_str("## 🚫 Forbidden Tasks\n\n");
+
+    let mut tasks: Vec<_> = issues.forbidden_usages.iter().collect();
+    tasks.sort_by_key(|(task, _)| task.as_str());
+
+    for (task, implementations) in tasks {
+        md.push_str(&format!("### 🔧 {}\n\n", task));
+        for impl_ in implementations {
+            md.push_str(&format!(
+                "- Version {} in {} ({}){}\n",
+                impl_.version,
+                markdown_location(impl_, issues),
+                impl_.repo_name,
+                markdown_source(impl_)
+            ));
+        }
+        md.push('\n');
+    }
+
+    Ok(())
+}
+
+/// Links `file:line` to the analyzed line in the provider's web UI. Without
+/// a link (unknown providers and local checkouts) it is plain code.
+fn markdown_location(impl_: &TaskImplementation, issues: &TaskIssues) -> String {
+    let location = format!("`{}:{}`", impl_.file_path.display(), impl_.line);
+    match issues.file_url(impl_) {
+        Some(link) => format!("[{}]({})", location, link),
+        None => location,
+    }
+}
+
+/// The source line as inline code on a continuation line of the list item.
+fn markdown_source(impl_: &TaskImplementation) -> String {
+    if impl_.source.is_empty() {
+        return String::new();
+    }
+    if impl_.source.contains('`') {
+        format!("\n  `` {} ``", impl_.source)
+    } else {
+        format!("\n  `{}`", impl_.source)
+    }
+}
+
+fn generate_missing_required_section(md: &mut String, issues: &TaskIssues) -> Result<()> {
+    md.push_str("## ❌ Missing Required Tasks\n\n");
+
+    let mut repos: Vec<_> = issues.missing_required_tasks.iter().collect();
+    repos.sort_by_key(|(repo, _)| repo.as_str());
+
+    for (repo, tasks) in repos {
+        let mut tasks = tasks.clone();
+        tasks.sort();
+        md.push_str(&format!("- {}: {}\n", repo, tasks.join(", ")));
+    }
+    md.push('\n');
+
+    Ok(())
+}
+
 fn generate_missing_states_section(md: &mut String, issues: &TaskIssues) -> Result<()> {
     md.push_str("## ❌ Tasks with Missing Valid States\n\n");
 
@@ -234,19 +623,14 @@ fn generate_valid_states_section(
 
         if let Some(implementations) = issues.all_implementations.get(task_name) {
             // Only include if all implementations are valid (not in invalid_states)
-            if !issues.invalid_states.contains_key(task_name) && !implementations.is_empty() {
+            if !issues.invalid_states.contains_key(task_name)
+                && !issues.forbidden_usages.contains_key(task_name)
+                && !implementations.is_empty()
+            {
                 // Collect unique repo names
                 let repos: Vec<_> = implementations
                     .iter()
-                    .map(|impl_| {
-                        impl_
-                            .repo_name
-                            .split('/')
-                            .last()
-                            .unwrap_or(&impl_.repo_name)
-                            .trim_end_matches("/_git/")
-                            .trim_end_matches(".git")
-                    })
+                    .map(|impl_| RepositoryUrl::parse(&impl_.repo_name).display_name())
                     .collect::<HashSet<_>>()
                     .into_iter()
                     .collect();
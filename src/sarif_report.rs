@@ -0,0 +1,257 @@
+use crate::{
+    error::Result, json::JsonValue, json_report::path_string, report::ReportRenderer, Config,
+    TaskImplementation, TaskIssues,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The kinds of findings reported as SARIF results, in `RULES` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Rule {
+    InvalidVersion,
+    ForbiddenTask,
+    UnknownTask,
+    ParseError,
+}
+
+const RULES: [Rule; 4] = [
+    Rule::InvalidVersion,
+    Rule::ForbiddenTask,
+    Rule::UnknownTask,
+    Rule::ParseError,
+];
+
+impl Rule {
+    fn id(&self) -> &'static str {
+        match self {
+            Rule::InvalidVersion => "ciprobe/invalid-version",
+            Rule::ForbiddenTask => "ciprobe/forbidden-task",
+            Rule::UnknownTask => "ciprobe/unknown-task",
+            Rule::ParseError => "ciprobe/parse-error",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Rule::InvalidVersion => "InvalidTaskVersion",
+            Rule::ForbiddenTask => "ForbiddenTask",
+            Rule::UnknownTask => "UnknownTask",
+            Rule::ParseError => "PipelineParseError",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Rule::InvalidVersion => "Task version is not allowed by the ciprobe configuration",
+            Rule::ForbiddenTask => "Task is forbidden by the ciprobe configuration",
+            Rule::UnknownTask => "Task is not listed in the ciprobe configuration",
+            Rule::ParseError => "Pipeline file could not be parsed, so its tasks were not checked",
+        }
+    }
+
+    fn level(&self) -> &'static str {
+        match self {
+            Rule::InvalidVersion | Rule::ForbiddenTask | Rule::ParseError => "error",
+            Rule::UnknownTask => "warning",
+        }
+    }
+
+    fn to_json(self) -> JsonValue {
+        JsonValue::object([
+            ("id", self.id().into()),
+            ("name", self.name().into()),
+            (
+                "shortDescription",
+                JsonValue::object([("text", self.description().into())]),
+            ),
+            (
+                "defaultConfiguration",
+                JsonValue::object([("level", self.level().into())]),
+            ),
+        ])
+    }
+}
+
+/// Renders a SARIF 2.1.0 log with one run per repository, so file paths stay
+/// relative to the repository they belong to.
+pub fn generate_sarif_report(
+    _repos: &[String],
+    config: &Config,
+    issues: &TaskIssues,
+) -> Result<String> {
+    let mut findings: BTreeMap<&str, Vec<(Rule, &str, &TaskImplementation)>> = BTreeMap::new();
+
+    for (task, repos) in &issues.invalid_states {
+        for impl_ in repos.values().flatten() {
+            findings
+                .entry(&impl_.repo_name)
+                .or_default()
+                .push((Rule::InvalidVersion, task, impl_));
+        }
+    }
+    for (task, impls) in &issues.forbidden_usages {
+        for impl_ in impls {
+            findings
+                .entry(&impl_.repo_name)
+                .or_default()
+                .push((Rule::ForbiddenTask, task, impl_));
+        }
+    }
+    for task in issues.missing_states.keys() {
+        for impl_ in issues.all_implementations.get(task).into_iter().flatten() {
+            findings
+                .entry(&impl_.repo_name)
+                .or_default()
+                .push((Rule::UnknownTask, task, impl_));
+        }
+    }
+
+    let repos: BTreeSet<&str> = findings
+        .keys()
+        .copied()
+        .chain(issues.files_failed.keys().map(String::as_str))
+        .collect();
+    let runs =
+        repos.into_iter().map(|repo| {
+            let mut task_results = findings.remove(repo).unwrap_or_default();
+            task_results.sort();
+            let mut results: Vec<_> = task_results
+                .into_iter()
+                .map(|(rule, task, impl_)| {
+                    result(
+                        rule,
+                        message(rule, task, impl_, config),
+                        location(&impl_.file_path, Some(impl_)),
+                    )
+                })
+                .collect();
+
+            let mut failed_files: Vec<_> = issues
+                .files_failed
+                .get(repo)
+                .into_iter()
+                .flatten()
+                .collect();
+            failed_files.sort();
+            results.extend(failed_files.into_iter().map(|(file, error)| {
+                result(Rule::ParseError, error.clone(), location(file, None))
+            }));
+
+            run(repo, issues.repo_commits.get(repo), results)
+        });
+
+    let log = JsonValue::object([
+        ("$schema", SARIF_SCHEMA.into()),
+        ("version", "2.1.0".into()),
+        ("runs", JsonValue::array(runs)),
+    ]);
+
+    Ok(log.to_pretty_string())
+}
+
+fn run(repo: &str, commit: Option<&String>, results: Vec<JsonValue>) -> JsonValue {
+    // Shared template repositories are identified as `url#ref`
+    let (repository_uri, reference) = match repo.split_once('#') {
+        Some((url, reference)) => (url, Some(reference)),
+        None => (repo, None),
+    };
+    let mut provenance = vec![("repositoryUri", repository_uri.into())];
+    if let Some(reference) = reference {
+        provenance.push(("branch", reference.into()));
+    }
+    if let Some(commit) = commit {
+        provenance.push(("revisionId", commit.into()));
+    }
+
+    JsonValue::object([
+        (
+            "tool",
+            JsonValue::object([(
+                "driver",
+                JsonValue::object([
+                    ("name", "ciprobe".into()),
+                    ("version", env!("CARGO_PKG_VERSION").into()),
+                    (
+                        "informationUri",
+                        "https://github.com/bastionlabs/ciprobe".into(),
+                    ),
+                    ("rules", JsonValue::array(RULES.map(Rule::to_json))),
+                ]),
+            )]),
+        ),
+        (
+            "versionControlProvenance",
+            JsonValue::array([JsonValue::object(provenance)]),
+        ),
+        ("results", JsonValue::Array(results)),
+    ])
+}
+
+fn message(rule: Rule, task: &str, impl_: &TaskImplementation, config: &Config) -> String {
+    match rule {
+        Rule::InvalidVersion => format!(
+            "Task '{}' uses version {}, expected {}",
+            task,
+            impl_.version,
+            config.get_valid_versions(task).join(" or ")
+        ),
+        Rule::ForbiddenTask => format!("Task '{}' (version {}) is forbidden", task, impl_.version),
+        Rule::UnknownTask => format!(
+            "Task '{}' (version {}) is not in the configuration",
+            task, impl_.version
+        ),
+        Rule::ParseError => unreachable!("parse errors carry the parser's message"),
+    }
+}
+
+fn result(rule: Rule, message: String, location: JsonValue) -> JsonValue {
+    JsonValue::object([
+        ("ruleId", rule.id().into()),
+        ("ruleIndex", (rule as usize).into()),
+        ("level", rule.level().into()),
+        ("message", JsonValue::object([("text", message.into())])),
+        ("locations", JsonValue::array([location])),
+    ])
+}
+
+/// The location of `file`, narrowed to the task when `impl_` is given.
+fn location(file: &Path, impl_: Option<&TaskImplementation>) -> JsonValue {
+    let mut physical = vec![(
+        "artifactLocation",
+        JsonValue::object([("uri", path_string(file).into())]),
+    )];
+    if let Some(impl_) = impl_ {
+        let mut region = vec![
+            ("startLine", impl_.line.into()),
+            ("startColumn", impl_.column.into()),
+        ];
+        if !impl_.source.is_empty() {
+            region.push((
+                "snippet",
+                JsonValue::object([("text", impl_.source.as_str().into())]),
+            ));
+        }
+        physical.push(("region", JsonValue::object(region)));
+    }
+
+    JsonValue::object([("physicalLocation", JsonValue::object(physical))])
+}
+
+#[derive(Debug)]
+pub struct SarifRenderer;
+
+impl ReportRenderer for SarifRenderer {
+    fn name(&self) -> &'static str {
+        "sarif"
+    }
+
+    fn default_output(&self) -> &'static str {
+        "report.sarif"
+    }
+
+    fn render(&self, repos: &[String], config: &Config, issues: &TaskIssues) -> Result<String> {
+        generate_sarif_report(repos, config, issues)
+    }
+}
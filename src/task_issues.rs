@@ -1,6 +1,9 @@
-use crate::{Config, TaskImplementation};
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use crate::{
+    console, url_provider::redact_credentials, Config, RepoEntry, RepositoryUrl,
+    TaskImplementation, TaskStatus,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 #[derive(Default)]
 pub struct TaskIssues {
@@ -8,8 +11,14 @@ pub struct TaskIssues {
     pub invalid_states: HashMap<String, HashMap<String, Vec<TaskImplementation>>>,
     pub missing_states: HashMap<String, String>, // normalized_name, original_name
     pub all_implementations: HashMap<String, Vec<TaskImplementation>>,
+    pub forbidden_usages: HashMap<String, Vec<TaskImplementation>>,
     pub repos_analyzed: HashSet<String>,
     pub repos_skipped: HashSet<String>,
+    pub repos_failed: HashMap<String, String>, // repo, error
+    pub files_failed: HashMap<String, Vec<(PathBuf, String)>>, // repo, (file, parse error)
+    pub repo_metadata: HashMap<String, RepoEntry>, // repo, inventory entry
+    pub repo_branches: HashMap<String, String>, // repo, branch or ref analyzed
+    pub repo_commits: HashMap<String, String>, // repo, commit SHA analyzed
 }
 
 impl TaskIssues {
@@ -20,70 +29,156 @@ impl TaskIssues {
             .push(task_name.to_string());
     }
 
-    pub fn add_invalid_state(
-        &mut self,
-        task_name: &str,
-        repo_name: &str,
-        version: String,
-        file_path: PathBuf,
-    ) {
+    pub fn add_failed_repo(&mut self, repo_name: &str, error: &str) {
+        self.repos_failed
+            .insert(repo_name.to_string(), redact_credentials(error));
+    }
+
+    /// Records a pipeline file whose tasks are unknown because it could not
+    /// be parsed.
+    pub fn add_failed_file(&mut self, repo_name: &str, file_path: &Path, error: &str) {
+        self.files_failed
+            .entry(repo_name.to_string())
+            .or_default()
+            .push((file_path.to_path_buf(), error.to_string()));
+    }
+
+    /// Adds the results of another analysis, e.g. of a single repository.
+    pub fn merge(&mut self, other: TaskIssues) {
+        for (repo, tasks) in other.missing_required_tasks {
+            self.missing_required_tasks
+                .entry(repo)
+                .or_default()
+                .extend(tasks);
+        }
+        for (task, repos) in other.invalid_states {
+            let invalid = self.invalid_states.entry(task).or_default();
+            for (repo, implementations) in repos {
+                invalid.entry(repo).or_default().extend(implementations);
+            }
+        }
+        for (task, implementations) in other.all_implementations {
+            self.all_implementations
+                .entry(task)
+                .or_default()
+                .extend(implementations);
+        }
+        for (task, implementations) in other.forbidden_usages {
+            self.forbidden_usages
+                .entry(task)
+                .or_default()
+                .extend(implementations);
+        }
+        self.missing_states.extend(other.missing_states);
+        self.repos_analyzed.extend(other.repos_analyzed);
+        self.repos_skipped.extend(other.repos_skipped);
+        self.repos_failed.extend(other.repos_failed);
+        for (repo, files) in other.files_failed {
+            self.files_failed.entry(repo).or_default().extend(files);
+        }
+        self.repo_metadata.extend(other.repo_metadata);
+        self.repo_branches.extend(other.repo_branches);
+        self.repo_commits.extend(other.repo_commits);
+    }
+
+    /// Links to the line of `implementation` on the commit it was found in.
+    pub fn file_url(&self, implementation: &TaskImplementation) -> Option<String> {
+        RepositoryUrl::parse(&implementation.repo_name).file_url(
+            &implementation.file_path,
+            implementation.line,
+            self.repo_commits
+                .get(&implementation.repo_name)
+                .map(String::as_str),
+        )
+    }
+
+    pub fn add_repo_metadata(&mut self, entry: &RepoEntry) {
+        self.repo_metadata.insert(entry.identity(), entry.clone());
+    }
+
+    /// The owning team of a repository, as given in the inventory.
+    pub fn team(&self, repo_name: &str) -> Option<&str> {
+        self.repo_metadata.get(repo_name)?.team.as_deref()
+    }
+
+    /// Repositories analyzed on more than one branch (or ref), with the
+    /// identity of each branch's results, e.g. `url#main` and `url#release/1`.
+    pub fn branch_groups(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (repo, entry) in &self.repo_metadata {
+            groups.entry(&entry.url).or_default().push(repo);
+        }
+        groups.retain(|_, repos| repos.len() > 1);
+        for repos in groups.values_mut() {
+            repos.sort();
+        }
+        groups
+    }
+
+    /// The versions of each task used in a repository.
+    pub fn task_versions(&self, repo_name: &str) -> BTreeMap<&str, BTreeSet<&str>> {
+        let mut versions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (task, implementations) in &self.all_implementations {
+            for implementation in implementations {
+                if implementation.repo_name == repo_name {
+                    versions
+                        .entry(task)
+                        .or_default()
+                        .insert(&implementation.version);
+                }
+            }
+        }
+        versions
+    }
+
+    pub fn add_invalid_state(&mut self, task_name: &str, implementation: TaskImplementation) {
         self.invalid_states
             .entry(task_name.to_string())
             .or_default()
-            .entry(repo_name.to_string())
+            .entry(implementation.repo_name.clone())
             .or_default()
-            .push(TaskImplementation {
-                repo_name: repo_name.to_string(),
-                version,
-                file_path,
-            });
+            .push(implementation);
     }
 
     pub fn add_implementation(
         &mut self,
         task_name: &str,
-        repo_name: &str,
-        version: String,
-        file_path: PathBuf,
+        implementation: TaskImplementation,
         config: &Config,
         verbose: bool,
     ) {
         let normalized_task_name = task_name.to_lowercase();
+        let repo_name = implementation.repo_name.as_str();
+        let version = &implementation.version;
 
-        let short_repo = repo_name
-            .split('/')
-            .last()
-            .unwrap_or(repo_name)
-            .trim_end_matches("/_git/")
-            .trim_end_matches(".git");
+        let short_repo = RepositoryUrl::parse(repo_name).display_name();
 
         if verbose {
-            println!(
+            console!(
                 "📝 Adding implementation for task '{}' (v{}) from repo '{}'",
-                normalized_task_name, version, short_repo
+                normalized_task_name,
+                version,
+                short_repo
             );
         }
 
         self.all_implementations
             .entry(normalized_task_name.clone())
             .or_default()
-            .push(TaskImplementation {
-                repo_name: repo_name.to_string(),
-                version: version.clone(),
-                file_path: file_path.clone(),
-            });
-
-        let valid_versions = config
-            .task_versions
-            .get(&normalized_task_name)
-            .cloned()
-            .unwrap_or_default();
-
-        if valid_versions.is_empty() {
-            self.missing_states
-                .insert(normalized_task_name.clone(), task_name.to_string());
-        } else if !valid_versions.contains(&version) {
-            self.add_invalid_state(&normalized_task_name, repo_name, version, file_path);
+            .push(implementation.clone());
+
+        match config.validate(&normalized_task_name, version) {
+            TaskStatus::Valid => {}
+            TaskStatus::Invalid => self.add_invalid_state(&normalized_task_name, implementation),
+            TaskStatus::Forbidden => self
+                .forbidden_usages
+                .entry(normalized_task_name)
+                .or_default()
+                .push(implementation),
+            TaskStatus::Unknown => {
+                self.missing_states
+                    .insert(normalized_task_name, task_name.to_string());
+            }
         }
     }
 }
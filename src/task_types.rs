@@ -5,11 +5,111 @@ pub enum SupportedTask {
     Default(String),
 }
 
+/// How a task or action reference pins its version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionKind {
+    /// `2`, `v4`
+    Major,
+    /// `1.2.3`, `v1.4`
+    Semver,
+    /// `main`, `release/1.x`
+    Branch,
+    /// A commit SHA (7 to 40 hex characters).
+    Commit,
+}
+
+impl VersionKind {
+    pub fn classify(version: &str) -> Self {
+        let numeric = version
+            .strip_prefix(['v', 'V'])
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(version);
+        let parts: Vec<_> = numeric.split('.').collect();
+
+        if parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        {
+            if parts.len() == 1 {
+                VersionKind::Major
+            } else {
+                VersionKind::Semver
+            }
+        } else if (7..=40).contains(&version.len())
+            && version.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            VersionKind::Commit
+        } else {
+            VersionKind::Branch
+        }
+    }
+}
+
+impl std::fmt::Display for VersionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionKind::Major => write!(f, "major"),
+            VersionKind::Semver => write!(f, "semver"),
+            VersionKind::Branch => write!(f, "branch"),
+            VersionKind::Commit => write!(f, "commit"),
+        }
+    }
+}
+
+/// The outcome of checking a task version against the configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskStatus {
+    Valid,
+    Invalid,
+    Forbidden,
+    /// The task is not listed in the configuration.
+    Unknown,
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskStatus::Valid => write!(f, "valid"),
+            TaskStatus::Invalid => write!(f, "invalid"),
+            TaskStatus::Forbidden => write!(f, "forbidden"),
+            TaskStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct TaskImplementation {
     pub repo_name: String,
     pub version: String,
     pub file_path: PathBuf,
+    /// 1-based position of the reference in `file_path`.
+    pub line: usize,
+    pub column: usize,
+    /// The trimmed source line, e.g. `- task: UseNode@1`.
+    pub source: String,
+    pub include_chain: Vec<PathBuf>,
+    /// Repositories that include this file as a shared template.
+    pub consumers: Vec<String>,
+}
+
+impl TaskImplementation {
+    pub fn version_kind(&self) -> VersionKind {
+        VersionKind::classify(&self.version)
+    }
+
+    /// `file:line:column`, the way compilers report positions.
+    pub fn location(&self) -> String {
+        format!("{}:{}:{}", self.file_path.display(), self.line, self.column)
+    }
+
+    /// Describes how the file was reached, e.g. `azure-pipelines.yml → templates/build.yml`.
+    pub fn include_path(&self) -> String {
+        self.include_chain
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
 }
 
 impl std::str::FromStr for SupportedTask {
@@ -0,0 +1,238 @@
+use crate::{
+    azure_pipeline::{AzurePipeline, RepositoryResource, TaskStep},
+    console,
+    error::Result,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A task together with the file it was written in and the chain of files
+/// (root pipeline first) through which it is reached.
+#[derive(Debug, Clone)]
+pub struct ResolvedTask {
+    pub task: TaskStep,
+    pub file_path: PathBuf,
+    pub include_chain: Vec<PathBuf>,
+}
+
+/// A `template: file@alias` reference into another repository, together with
+/// the chain of local files that leads to it.
+#[derive(Debug, Clone)]
+pub struct ExternalTemplateReference {
+    pub repository: RepositoryResource,
+    pub path: PathBuf,
+    pub include_chain: Vec<PathBuf>,
+}
+
+/// Everything reached by walking the root pipelines of a repository.
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub tasks: Vec<ResolvedTask>,
+    /// `file@alias` templates, with the alias looked up in the root's
+    /// `resources.repositories`.
+    pub external_templates: Vec<ExternalTemplateReference>,
+}
+
+/// Follows local `template:` references between the pipeline files of a
+/// single repository. All paths it returns are relative to the repo root.
+pub struct TemplateResolver {
+    repo_root: PathBuf,
+    pipelines: HashMap<PathBuf, AzurePipeline>,
+    /// Files that could not be parsed, with the error.
+    parse_failures: Vec<(PathBuf, String)>,
+    verbose: bool,
+}
+
+impl TemplateResolver {
+    pub fn load(repo_root: &Path, files: &[PathBuf], verbose: bool) -> Result<Self> {
+        let mut pipelines = HashMap::new();
+        let mut parse_failures = Vec::new();
+
+        for file in files {
+            let relative = file.strip_prefix(repo_root).unwrap_or(file).to_path_buf();
+            if verbose {
+                console!("📄 Analyzing pipeline file: {}", relative.display());
+            }
+            let content = fs::read_to_string(file)?;
+            match AzurePipeline::parse(&content) {
+                Ok(pipeline) => {
+                    pipelines.insert(relative, pipeline);
+                }
+                Err(e) => {
+                    console!("   └─ ⚠️ Skipping {}: {}", relative.display(), e);
+                    parse_failures.push((relative, e.to_string()));
+                }
+            }
+        }
+
+        Ok(Self {
+            repo_root: repo_root.to_path_buf(),
+            pipelines,
+            parse_failures,
+            verbose,
+        })
+    }
+
+    /// The files `load` skipped because they could not be parsed.
+    pub fn parse_failures(&self) -> &[(PathBuf, String)] {
+        &self.parse_failures
+    }
+
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Resolves a `template:` path the way Azure Pipelines does: relative to
+    /// the including file, or to the repo root when it starts with `/`.
+    /// Returns `None` for references into other repositories (`file@alias`)
+    /// and for paths built from template expressions.
+    pub fn resolve_template_path(including_file: &Path, reference: &str) -> Option<PathBuf> {
+        let reference = reference.trim();
+        let reference = reference.strip_suffix("@self").unwrap_or(reference);
+        if reference.is_empty() || reference.contains("${{") || reference.contains('@') {
+            return None;
+        }
+
+        let joined = match reference.strip_prefix('/') {
+            Some(from_root) => PathBuf::from(from_root),
+            None => including_file
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(reference),
+        };
+
+        let mut normalized = PathBuf::new();
+        for component in joined.components() {
+            match component {
+                // `..` above the repo root cannot be resolved
+                Component::ParentDir if !normalized.pop() => return None,
+                Component::Normal(part) => normalized.push(part),
+                _ => {}
+            }
+        }
+        Some(normalized)
+    }
+
+    /// Files that no other file includes as a template.
+    pub fn root_files(&self) -> Vec<PathBuf> {
+        let included: HashSet<PathBuf> = self
+            .pipelines
+            .iter()
+            .flat_map(|(file, pipeline)| {
+                pipeline
+                    .templates()
+                    .filter_map(|t| Self::resolve_template_path(file, &t.path))
+            })
+            .collect();
+
+        let mut roots: Vec<PathBuf> = self
+            .pipelines
+            .keys()
+            .filter(|file| !included.contains(*file))
+            .cloned()
+            .collect();
+        roots.sort();
+        roots
+    }
+
+    /// Walks every root pipeline and returns the tasks it reaches, including
+    /// those written in the templates it pulls in, and the templates it
+    /// includes from other repositories.
+    pub fn resolve(&self) -> Resolution {
+        let mut resolution = Resolution::default();
+        for root in self.root_files() {
+            // Only the root pipeline may declare resources
+            let root_pipeline = self.pipelines.get(&root);
+            self.walk(
+                &root,
+                &mut vec![root.clone()],
+                root_pipeline,
+                &mut resolution,
+            );
+        }
+        resolution
+    }
+
+    /// Walks the templates reachable from `entry_file` only, as done for a
+    /// template that another repository includes.
+    pub fn resolve_from(&self, entry_file: &Path) -> Vec<ResolvedTask> {
+        let mut resolution = Resolution::default();
+        let entry_file = entry_file.to_path_buf();
+        self.walk(
+            &entry_file,
+            &mut vec![entry_file.clone()],
+            None,
+            &mut resolution,
+        );
+        resolution.tasks
+    }
+
+    fn walk(
+        &self,
+        file: &Path,
+        chain: &mut Vec<PathBuf>,
+        root: Option<&AzurePipeline>,
+        resolution: &mut Resolution,
+    ) {
+        let Some(pipeline) = self.pipelines.get(file) else {
+            return;
+        };
+
+        for task in pipeline.tasks() {
+            resolution.tasks.push(ResolvedTask {
+                task: task.clone(),
+                file_path: file.to_path_buf(),
+                include_chain: chain.clone(),
+            });
+        }
+
+        for template in pipeline.templates() {
+            let Some(target) = Self::resolve_template_path(file, &template.path) else {
+                if let Some(external) = Self::external_reference(&template.path, root, chain) {
+                    resolution.external_templates.push(external);
+                }
+                continue;
+            };
+
+            if chain.contains(&target) {
+                if self.verbose {
+                    console!("   └─ ⚠️ Skipping recursive template {}", target.display());
+                }
+                continue;
+            }
+
+            if !self.pipelines.contains_key(&target) {
+                if self.verbose {
+                    console!(
+                        "   └─ ⚠️ Template {} referenced from {} not found",
+                        target.display(),
+                        file.display()
+                    );
+                }
+                continue;
+            }
+
+            chain.push(target.clone());
+            self.walk(&target, chain, root, resolution);
+            chain.pop();
+        }
+    }
+
+    fn external_reference(
+        reference: &str,
+        root: Option<&AzurePipeline>,
+        chain: &[PathBuf],
+    ) -> Option<ExternalTemplateReference> {
+        let (path, alias) = reference.trim().rsplit_once('@')?;
+        if path.contains("${{") {
+            return None;
+        }
+        let repository = root?.repository(alias)?.clone();
+        Some(ExternalTemplateReference {
+            repository,
+            path: PathBuf::from(path.trim_start_matches('/')),
+            include_chain: chain.to_vec(),
+        })
+    }
+}
@@ -0,0 +1,867 @@
+use crate::error::{Error, Result};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlValue {
+    Null,
+    Scalar(String),
+    Sequence(Vec<YamlNode>),
+    Mapping(Vec<(String, YamlNode)>),
+}
+
+/// A parsed YAML value together with the 1-based position it starts at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlNode {
+    pub value: YamlValue,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl YamlNode {
+    fn new(value: YamlValue, line: usize, column: usize) -> Self {
+        Self {
+            value,
+            line,
+            column,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&YamlNode> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            YamlValue::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[YamlNode]> {
+        match &self.value {
+            YamlValue::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(String, YamlNode)]> {
+        match &self.value {
+            YamlValue::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.value == YamlValue::Null
+    }
+}
+
+/// Parses every document in `content` (documents are separated by `---`).
+///
+/// This covers the subset of YAML used by CI pipeline files: block and flow
+/// collections, plain/quoted/block scalars, comments, anchors, aliases and
+/// merge keys.
+pub fn parse_documents(content: &str) -> Result<Vec<YamlNode>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut documents = Vec::new();
+    let mut start = 0;
+    for (idx, line) in lines.iter().enumerate() {
+        let is_separator = *line == "---" || line.starts_with("--- ") || *line == "...";
+        if is_separator || line.starts_with('%') {
+            if lines[start..idx].iter().any(|l| is_significant(l)) {
+                documents.push(Parser::new(&lines[start..idx], start).parse()?);
+            }
+            start = idx + 1;
+        }
+    }
+    if lines[start..].iter().any(|l| is_significant(l)) {
+        documents.push(Parser::new(&lines[start..], start).parse()?);
+    }
+
+    Ok(documents)
+}
+
+/// Parses the first document in `content`, returning a null node for empty input.
+pub fn parse_document(content: &str) -> Result<YamlNode> {
+    Ok(parse_documents(content)?
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| YamlNode::new(YamlValue::Null, 1, 1)))
+}
+
+/// The trimmed text of the 1-based `line` of `content`, as reported by
+/// `YamlNode::line`.
+pub fn source_line(content: &str, line: usize) -> String {
+    content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .nth(line.saturating_sub(1))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn indent_of(line: &str) -> usize {
+    line.chars().take_while(|c| *c == ' ' || *c == '\t').count()
+}
+
+fn is_significant(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ") || content.starts_with("-\t")
+}
+
+fn column_of(line: &str, byte_offset: usize) -> usize {
+    line[..byte_offset].chars().count() + 1
+}
+
+/// Removes a trailing comment, ignoring `#` characters inside quoted text.
+fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut prev: Option<char> = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        match quote {
+            Some('\'') if c == '\'' => {
+                if chars.peek().map(|(_, n)| *n) == Some('\'') {
+                    chars.next();
+                } else {
+                    quote = None;
+                }
+            }
+            Some('"') if c == '\\' => {
+                chars.next();
+            }
+            Some('"') if c == '"' => quote = None,
+            Some(_) => {}
+            None => {
+                let at_token_start =
+                    prev.is_none_or(|p| p.is_whitespace() || matches!(p, '[' | '{' | ',' | ':'));
+                if (c == '\'' || c == '"') && at_token_start {
+                    quote = Some(c);
+                } else if c == '#' && prev.is_none_or(char::is_whitespace) {
+                    return text[..idx].trim_end();
+                }
+            }
+        }
+        prev = Some(c);
+    }
+
+    text.trim_end()
+}
+
+/// Finds the `:` separating a mapping key from its value, returning the key and
+/// the byte offset where the value starts.
+fn split_mapping_entry(content: &str) -> Option<(String, usize)> {
+    if is_sequence_item(content) || content.starts_with(['{', '[', '?', '|', '>', '*', '&', '!']) {
+        return None;
+    }
+
+    let bytes = content.as_bytes();
+    if content.starts_with('"') || content.starts_with('\'') {
+        let quote = content.chars().next()?;
+        let end = find_closing_quote(content, quote)?;
+        let rest = &content[end + 1..];
+        let after = rest.trim_start();
+        if !after.starts_with(':') {
+            return None;
+        }
+        let colon = content.len() - after.len();
+        if colon + 1 < content.len() && !bytes[colon + 1].is_ascii_whitespace() {
+            return None;
+        }
+        return Some((unquote(&content[..=end]), colon + 1));
+    }
+
+    let mut depth = 0i32;
+    for (idx, c) in content.char_indices() {
+        match c {
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth -= 1,
+            ':' if depth <= 0 => {
+                let next = bytes.get(idx + 1);
+                if next.is_none_or(|b| b.is_ascii_whitespace()) {
+                    let key = content[..idx].trim_end();
+                    if key.is_empty() {
+                        return None;
+                    }
+                    return Some((key.to_string(), idx + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((idx, c)) = chars.next() {
+        if quote == '"' && c == '\\' {
+            chars.next();
+        } else if c == quote {
+            if quote == '\'' && chars.peek().map(|(_, n)| *n) == Some('\'') {
+                chars.next();
+            } else {
+                return Some(idx);
+            }
+        }
+    }
+    None
+}
+
+fn unquote(text: &str) -> String {
+    let text = text.trim();
+    if text.len() >= 2 && text.starts_with('\'') && text.ends_with('\'') {
+        text[1..text.len() - 1].replace("''", "'")
+    } else if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+        unescape_double_quoted(&text[1..text.len() - 1])
+    } else {
+        text.to_string()
+    }
+}
+
+fn unescape_double_quoted(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+struct Parser {
+    lines: Vec<String>,
+    line_offset: usize,
+    pos: usize,
+    anchors: HashMap<String, YamlNode>,
+}
+
+impl Parser {
+    fn new(lines: &[&str], line_offset: usize) -> Self {
+        Self {
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+            line_offset,
+            pos: 0,
+            anchors: HashMap::new(),
+        }
+    }
+
+    fn line_number(&self, idx: usize) -> usize {
+        self.line_offset + idx + 1
+    }
+
+    fn error(&self, idx: usize, message: &str) -> Error {
+        Error::Parse(format!("line {}: {}", self.line_number(idx), message))
+    }
+
+    fn parse(mut self) -> Result<YamlNode> {
+        let node = self.parse_block(0, -1)?;
+        if let Some(idx) = self.peek_significant() {
+            return Err(self.error(idx, "unexpected content after document root"));
+        }
+        Ok(node)
+    }
+
+    /// Moves `pos` to the next line with content and returns its index.
+    fn peek_significant(&mut self) -> Option<usize> {
+        while self.pos < self.lines.len() && !is_significant(&self.lines[self.pos]) {
+            self.pos += 1;
+        }
+        (self.pos < self.lines.len()).then_some(self.pos)
+    }
+
+    fn content_at(&self, idx: usize) -> &str {
+        let line = &self.lines[idx];
+        strip_comment(&line[indent_of(line)..])
+    }
+
+    fn null_after(&self, idx: usize) -> YamlNode {
+        let line = &self.lines[idx];
+        YamlNode::new(
+            YamlValue::Null,
+            self.line_number(idx),
+            line.chars().count() + 1,
+        )
+    }
+
+    fn parse_block(&mut self, min_indent: usize, parent_indent: isize) -> Result<YamlNode> {
+        let Some(idx) = self.peek_significant() else {
+            let line = self.line_number(self.lines.len().saturating_sub(1));
+            return Ok(YamlNode::new(YamlValue::Null, line, 1));
+        };
+        let indent = indent_of(&self.lines[idx]);
+        if indent < min_indent {
+            return Ok(self.null_after(idx.saturating_sub(1)));
+        }
+        self.parse_node_at(idx, indent, parent_indent)
+    }
+
+    fn parse_node_at(
+        &mut self,
+        idx: usize,
+        indent: usize,
+        parent_indent: isize,
+    ) -> Result<YamlNode> {
+        let content = self.content_at(idx).to_string();
+        if is_sequence_item(&content) {
+            self.parse_sequence(indent)
+        } else if split_mapping_entry(&content).is_some() {
+            self.parse_mapping(indent)
+        } else {
+            self.parse_inline(idx, indent, parent_indent)
+        }
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<YamlNode> {
+        let mut items = Vec::new();
+        let mut position = None;
+
+        while let Some(idx) = self.peek_significant() {
+            let line_indent = indent_of(&self.lines[idx]);
+            if line_indent != indent || !is_sequence_item(self.content_at(idx)) {
+                if line_indent > indent {
+                    return Err(self.error(idx, "unexpected indentation in sequence"));
+                }
+                break;
+            }
+            position.get_or_insert((self.line_number(idx), indent + 1));
+
+            let after_dash = &self.lines[idx][indent + 1..];
+            let item_indent = indent + 1 + indent_of(after_dash);
+            let item = if strip_comment(after_dash).trim().is_empty() {
+                self.pos += 1;
+                match self.peek_significant() {
+                    Some(next) if indent_of(&self.lines[next]) > indent => {
+                        let next_indent = indent_of(&self.lines[next]);
+                        self.parse_node_at(next, next_indent, indent as isize)?
+                    }
+                    _ => self.null_after(idx),
+                }
+            } else {
+                // Re-read the item as if the dash were indentation, so that
+                // `- key: value` continues as a mapping on the following lines.
+                self.lines[idx].replace_range(indent..indent + 1, " ");
+                self.parse_node_at(idx, item_indent, indent as isize)?
+            };
+            items.push(item);
+        }
+
+        let (line, column) = position.unwrap_or((self.line_number(self.pos), indent + 1));
+        Ok(YamlNode::new(YamlValue::Sequence(items), line, column))
+    }
+
+    fn parse_mapping(&mut self, indent: usize) -> Result<YamlNode> {
+        let mut entries: Vec<(String, YamlNode)> = Vec::new();
+        let mut position = None;
+
+        while let Some(idx) = self.peek_significant() {
+            let line_indent = indent_of(&self.lines[idx]);
+            if line_indent < indent {
+                break;
+            }
+            if line_indent > indent {
+                return Err(self.error(idx, "unexpected indentation in mapping"));
+            }
+
+            let content = self.content_at(idx).to_string();
+            let Some((key, value_offset)) = split_mapping_entry(&content) else {
+                if is_sequence_item(&content) {
+                    break;
+                }
+                return Err(self.error(idx, "expected a mapping entry"));
+            };
+            position.get_or_insert((self.line_number(idx), indent + 1));
+
+            let value_start = indent + value_offset;
+            let rest = content[value_offset..].trim_start();
+            let value_col = value_start + (content.len() - value_offset - rest.len());
+            let value = self.parse_value(idx, rest, value_col, indent)?;
+
+            if key == "<<" {
+                let merged: Vec<(String, YamlNode)> = match &value.value {
+                    YamlValue::Mapping(m) => m.clone(),
+                    YamlValue::Sequence(items) => items
+                        .iter()
+                        .filter_map(|i| i.as_mapping())
+                        .flat_map(|m| m.iter().cloned())
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                for (k, v) in merged {
+                    if !entries.iter().any(|(existing, _)| *existing == k) {
+                        entries.push((k, v));
+                    }
+                }
+            } else if let Some(existing) = entries.iter_mut().find(|(k, _)| *k == key) {
+                existing.1 = value;
+            } else {
+                entries.push((key, value));
+            }
+        }
+
+        let (line, column) = position.unwrap_or((self.line_number(self.pos), indent + 1));
+        Ok(YamlNode::new(YamlValue::Mapping(entries), line, column))
+    }
+
+    /// Parses the value of a mapping entry whose text starts at byte `value_col`
+    /// of line `idx`. `rest` is the comment-free value text.
+    fn parse_value(
+        &mut self,
+        idx: usize,
+        rest: &str,
+        value_col: usize,
+        parent_indent: usize,
+    ) -> Result<YamlNode> {
+        let mut rest = rest.to_string();
+        let mut value_col = value_col;
+        let mut anchor = None;
+
+        // Anchors and tags may precede the actual value.
+        while rest.starts_with('&') || rest.starts_with('!') {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if rest.starts_with('&') {
+                anchor = Some(rest[1..end].to_string());
+            }
+            let remaining = rest[end..].trim_start();
+            value_col += rest.len() - remaining.len();
+            rest = remaining.to_string();
+        }
+
+        let value = if rest.is_empty() {
+            self.pos = idx + 1;
+            match self.peek_significant() {
+                Some(next) => {
+                    let next_indent = indent_of(&self.lines[next]);
+                    if next_indent > parent_indent {
+                        self.parse_node_at(next, next_indent, parent_indent as isize)?
+                    } else if next_indent == parent_indent
+                        && is_sequence_item(self.content_at(next))
+                    {
+                        self.parse_sequence(parent_indent)?
+                    } else {
+                        self.null_after(idx)
+                    }
+                }
+                None => self.null_after(idx),
+            }
+        } else {
+            let line = &self.lines[idx];
+            let column = column_of(line, value_col.min(line.len()));
+            self.parse_scalar_or_flow(idx, &rest, value_col, column, parent_indent as isize)?
+        };
+
+        if let Some(name) = anchor {
+            self.anchors.insert(name, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Parses a node that starts in the middle of line `idx` and is not a
+    /// block collection (a scalar, flow collection or alias).
+    fn parse_inline(
+        &mut self,
+        idx: usize,
+        indent: usize,
+        parent_indent: isize,
+    ) -> Result<YamlNode> {
+        let content = self.content_at(idx).to_string();
+        let column = column_of(&self.lines[idx], indent);
+        let node = self.parse_scalar_or_flow(idx, &content, indent, column, parent_indent)?;
+        Ok(node)
+    }
+
+    fn parse_scalar_or_flow(
+        &mut self,
+        idx: usize,
+        text: &str,
+        byte_col: usize,
+        column: usize,
+        parent_indent: isize,
+    ) -> Result<YamlNode> {
+        let line_no = self.line_number(idx);
+
+        if text.starts_with('|') || text.starts_with('>') {
+            return Ok(self.parse_block_scalar(idx, text, column, parent_indent));
+        }
+
+        if let Some(name) = text.strip_prefix('*') {
+            self.pos = idx + 1;
+            let mut node = self
+                .anchors
+                .get(name.trim())
+                .cloned()
+                .ok_or_else(|| self.error(idx, &format!("unknown alias '{}'", name.trim())))?;
+            node.line = line_no;
+            node.column = column;
+            return Ok(node);
+        }
+
+        if text.starts_with('{') || text.starts_with('[') {
+            return self.parse_flow_lines(idx, byte_col);
+        }
+
+        if text.starts_with('"') || text.starts_with('\'') {
+            let quote = text.chars().next().unwrap_or('"');
+            let mut joined = text.to_string();
+            self.pos = idx + 1;
+            while find_closing_quote(&joined, quote).is_none() && self.pos < self.lines.len() {
+                let next = self.lines[self.pos].trim();
+                joined.push(' ');
+                joined.push_str(next);
+                self.pos += 1;
+            }
+            let end = find_closing_quote(&joined, quote)
+                .ok_or_else(|| self.error(idx, "unterminated quoted scalar"))?;
+            return Ok(YamlNode::new(
+                YamlValue::Scalar(unquote(&joined[..=end])),
+                line_no,
+                column,
+            ));
+        }
+
+        // Plain scalars may continue on more-indented lines.
+        let mut value = text.to_string();
+        self.pos = idx + 1;
+        while let Some(next) = self.peek_significant() {
+            if indent_of(&self.lines[next]) as isize <= parent_indent {
+                break;
+            }
+            let continuation = self.content_at(next).to_string();
+            if split_mapping_entry(&continuation).is_some() || is_sequence_item(&continuation) {
+                return Err(self.error(next, "unexpected indentation after scalar"));
+            }
+            value.push(' ');
+            value.push_str(&continuation);
+            self.pos = next + 1;
+        }
+
+        Ok(YamlNode::new(YamlValue::Scalar(value), line_no, column))
+    }
+
+    fn parse_block_scalar(
+        &mut self,
+        idx: usize,
+        header: &str,
+        column: usize,
+        parent_indent: isize,
+    ) -> YamlNode {
+        let literal = header.starts_with('|');
+        let keep = header.contains('+');
+        let strip = header.contains('-');
+
+        let mut body: Vec<String> = Vec::new();
+        let mut content_indent = None;
+        self.pos = idx + 1;
+        while self.pos < self.lines.len() {
+            let line = &self.lines[self.pos];
+            if line.trim().is_empty() {
+                body.push(String::new());
+                self.pos += 1;
+                continue;
+            }
+            let indent = indent_of(line);
+            if indent as isize <= parent_indent {
+                break;
+            }
+            let base = *content_indent.get_or_insert(indent);
+            if indent < base {
+                break;
+            }
+            body.push(line[base..].to_string());
+            self.pos += 1;
+        }
+
+        let trailing_blank = body.iter().rev().take_while(|l| l.is_empty()).count();
+        let content_lines = &body[..body.len() - trailing_blank];
+        let mut value = if literal {
+            content_lines.join("\n")
+        } else {
+            content_lines
+                .iter()
+                .map(|l| if l.is_empty() { "\n" } else { l.as_str() })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace(" \n ", "\n")
+        };
+        if !strip && !content_lines.is_empty() {
+            value.push('\n');
+        }
+        if keep {
+            value.push_str(&"\n".repeat(trailing_blank));
+        }
+
+        YamlNode::new(YamlValue::Scalar(value), self.line_number(idx), column)
+    }
+
+    /// Collects a (possibly multi-line) flow collection starting at byte
+    /// `byte_col` of line `idx` and parses it.
+    fn parse_flow_lines(&mut self, idx: usize, byte_col: usize) -> Result<YamlNode> {
+        let mut chars: Vec<(char, usize, usize)> = Vec::new();
+        let mut depth = 0i32;
+        let mut quote: Option<char> = None;
+        let mut prev: Option<char> = None;
+        let mut line_idx = idx;
+        let mut start = byte_col;
+
+        'lines: while line_idx < self.lines.len() {
+            let line = self.lines[line_idx].clone();
+            let line_no = self.line_number(line_idx);
+            let text = if quote.is_some() {
+                &line[start..]
+            } else {
+                strip_comment(&line[start..])
+            };
+            let mut column = column_of(&line, start);
+            for c in text.chars() {
+                match quote {
+                    Some(q) if c == q => quote = None,
+                    Some(_) => {}
+                    // Quotes only open at the start of a scalar, as in `it's`
+                    None => match c {
+                        '"' | '\''
+                            if prev.is_none_or(|p| {
+                                p.is_whitespace() || matches!(p, '[' | '{' | ',' | ':')
+                            }) =>
+                        {
+                            quote = Some(c)
+                        }
+                        '{' | '[' => depth += 1,
+                        '}' | ']' => depth -= 1,
+                        _ => {}
+                    },
+                }
+                prev = Some(c);
+                chars.push((c, line_no, column));
+                column += 1;
+                if depth == 0 && quote.is_none() {
+                    self.pos = line_idx + 1;
+                    break 'lines;
+                }
+            }
+            chars.push((' ', line_no, column));
+            prev = Some(' ');
+            line_idx += 1;
+            start = 0;
+        }
+
+        if depth != 0 {
+            return Err(self.error(idx, "unterminated flow collection"));
+        }
+
+        let mut flow = FlowParser {
+            chars: &chars,
+            pos: 0,
+            anchors: &self.anchors,
+        };
+        flow.parse_value()
+            .ok_or_else(|| self.error(idx, "invalid flow collection"))
+    }
+}
+
+struct FlowParser<'a> {
+    chars: &'a [(char, usize, usize)],
+    pos: usize,
+    anchors: &'a HashMap<String, YamlNode>,
+}
+
+impl FlowParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|(c, _, _)| *c)
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.chars
+            .get(self.pos)
+            .or_else(|| self.chars.last())
+            .map(|(_, l, c)| (*l, *c))
+            .unwrap_or((1, 1))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<YamlNode> {
+        self.skip_whitespace();
+        let (line, column) = self.position();
+        match self.peek()? {
+            '{' => self.parse_mapping(),
+            '[' => self.parse_sequence(),
+            '"' | '\'' => {
+                let quote = self.peek()?;
+                let mut text = String::from(quote);
+                self.pos += 1;
+                while let Some(c) = self.peek() {
+                    text.push(c);
+                    self.pos += 1;
+                    if c == '\\' && quote == '"' {
+                        text.push(self.peek()?);
+                        self.pos += 1;
+                    } else if c == quote {
+                        if quote == '\'' && self.peek() == Some('\'') {
+                            text.push('\'');
+                            self.pos += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                Some(YamlNode::new(
+                    YamlValue::Scalar(unquote(&text)),
+                    line,
+                    column,
+                ))
+            }
+            _ => {
+                let text = self.parse_plain();
+                if let Some(name) = text.strip_prefix('*') {
+                    let mut node = self.anchors.get(name)?.clone();
+                    node.line = line;
+                    node.column = column;
+                    return Some(node);
+                }
+                let value = if text.is_empty() {
+                    YamlValue::Null
+                } else {
+                    YamlValue::Scalar(text)
+                };
+                Some(YamlNode::new(value, line, column))
+            }
+        }
+    }
+
+    fn parse_plain(&mut self) -> String {
+        let mut text = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            if depth == 0 {
+                if matches!(c, ',' | ']' | '}') {
+                    break;
+                }
+                if c == ':' {
+                    let next = self.chars.get(self.pos + 1).map(|(n, _, _)| *n);
+                    if next.is_none_or(|n| n.is_whitespace() || matches!(n, ',' | ']' | '}')) {
+                        break;
+                    }
+                }
+            }
+            // Template expressions such as `${{ eq(a, b) }}` contain commas
+            // and braces that must not end the scalar.
+            if c == '$' && self.chars.get(self.pos + 1).map(|(n, _, _)| *n) == Some('{') {
+                depth += 1;
+                text.push(c);
+                self.pos += 1;
+                text.push('{');
+                self.pos += 1;
+                continue;
+            }
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                '{' if depth > 0 => depth += 1,
+                '}' if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+            self.pos += 1;
+        }
+        text.trim().to_string()
+    }
+
+    fn parse_sequence(&mut self) -> Option<YamlNode> {
+        let (line, column) = self.position();
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                ']' => {
+                    self.pos += 1;
+                    break;
+                }
+                ',' => self.pos += 1,
+                // A mismatched closer, as in `[a}`, ends nothing
+                '}' => return None,
+                _ => {
+                    let start = self.pos;
+                    let item = self.parse_value()?;
+                    if self.pos == start {
+                        return None;
+                    }
+                    self.skip_whitespace();
+                    if self.peek() == Some(':') {
+                        // Single-pair mapping inside a sequence: `[a: b]`.
+                        self.pos += 1;
+                        let key = item.as_str().unwrap_or_default().to_string();
+                        let value = self.parse_value()?;
+                        items.push(YamlNode::new(
+                            YamlValue::Mapping(vec![(key, value)]),
+                            item.line,
+                            item.column,
+                        ));
+                    } else {
+                        items.push(item);
+                    }
+                }
+            }
+        }
+        Some(YamlNode::new(YamlValue::Sequence(items), line, column))
+    }
+
+    fn parse_mapping(&mut self) -> Option<YamlNode> {
+        let (line, column) = self.position();
+        self.pos += 1;
+        let mut entries: Vec<(String, YamlNode)> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                '}' => {
+                    self.pos += 1;
+                    break;
+                }
+                ',' => self.pos += 1,
+                ']' => return None,
+                _ => {
+                    let start = self.pos;
+                    let key_node = self.parse_value()?;
+                    if self.pos == start {
+                        return None;
+                    }
+                    let key = key_node.as_str().unwrap_or_default().to_string();
+                    self.skip_whitespace();
+                    let value = if self.peek() == Some(':') {
+                        self.pos += 1;
+                        self.parse_value()?
+                    } else {
+                        YamlNode::new(YamlValue::Null, key_node.line, key_node.column)
+                    };
+                    entries.push((key, value));
+                }
+            }
+        }
+        Some(YamlNode::new(YamlValue::Mapping(entries), line, column))
+    }
+}
@@ -1,11 +1,14 @@
+use crate::config::{TaskPolicy, VersionRequirement};
 use crate::error::{Error, Result};
+use crate::yaml_document::{parse_document, YamlNode, YamlValue};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 #[derive(Debug)]
 pub struct YamlConfig {
-    pub task_versions: HashMap<String, Vec<String>>,
+    pub task_versions: HashMap<String, Vec<VersionRequirement>>,
+    pub task_policies: HashMap<String, TaskPolicy>,
 }
 
 impl YamlConfig {
@@ -22,51 +25,65 @@ impl YamlConfig {
         Self::parse(&content)
     }
 
-    fn parse(content: &str) -> Result<Self> {
-        let mut task_versions = HashMap::new();
-        let mut current_indent = 0;
-        let mut in_task_versions = false;
-        let mut current_task = String::new();
-
-        for line in content.lines() {
-            let trimmed = line.trim_start();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
+    /// Parses `task_versions`, where each task is either a list of versions
+    /// or a mapping with a `policy` and an optional `versions` list:
+    ///
+    /// ```yaml
+    /// task_versions:
+    ///   'UseNode':
+    ///     - '1'
+    ///   'Docker':
+    ///     policy: forbidden
+    /// ```
+    pub fn parse(content: &str) -> Result<Self> {
+        let document = parse_document(content)
+            .map_err(|e| Error::Config(format!("Invalid config file: {}", e)))?;
+        let entries = document
+            .get("task_versions")
+            .and_then(|t| t.as_mapping())
+            .ok_or_else(|| {
+                Error::Config("Invalid config file: missing task_versions section".to_string())
+            })?;
 
-            let indent = line.len() - trimmed.len();
+        let mut task_versions = HashMap::new();
+        let mut task_policies = HashMap::new();
 
-            if trimmed.starts_with("task_versions:") {
-                in_task_versions = true;
-                current_indent = indent;
-                continue;
-            }
+        for (task_name, value) in entries {
+            let (policy, versions) = match &value.value {
+                YamlValue::Mapping(_) => {
+                    let policy = match value.get("policy").and_then(|p| p.as_str()) {
+                        Some(policy) => policy
+                            .parse()
+                            .map_err(|e| Error::Config(format!("Task '{}': {}", task_name, e)))?,
+                        None => TaskPolicy::default(),
+                    };
+                    let versions = value
+                        .get("versions")
+                        .map(|v| parse_versions(task_name, v))
+                        .transpose()?
+                        .unwrap_or_default();
+                    (policy, versions)
+                }
+                _ => (TaskPolicy::default(), parse_versions(task_name, value)?),
+            };
 
-            if in_task_versions && indent > current_indent {
-                if trimmed.ends_with(':') {
-                    let task_name = trimmed
-                        .trim_end_matches(':')
-                        .trim_matches('\'')
-                        .trim_matches('"')
-                        .trim();
-                    current_task = task_name.to_string();
-                    task_versions.insert(current_task.clone(), Vec::new());
-                } else if trimmed.starts_with('-') {
-                    let version = trimmed
-                        .trim_start_matches('-')
-                        .trim()
-                        .trim_matches('\'')
-                        .trim_matches('"')
-                        .trim();
-                    if !version.is_empty() && !current_task.is_empty() {
-                        if let Some(versions) = task_versions.get_mut(&current_task) {
-                            versions.push(version.to_string());
-                        }
-                    }
+            // A required task without versions only has to be present, a
+            // governed one could never be validated
+            let versions = match policy {
+                TaskPolicy::Required if versions.is_empty() => {
+                    vec![VersionRequirement::parse("*")?]
+                }
+                TaskPolicy::Governed if versions.is_empty() => {
+                    return Err(Error::Config(format!(
+                        "Task '{}': governed tasks need at least one version",
+                        task_name
+                    )));
                 }
-            } else if indent <= current_indent {
-                in_task_versions = false;
-            }
+                _ => versions,
+            };
+
+            task_versions.insert(task_name.clone(), versions);
+            task_policies.insert(task_name.clone(), policy);
         }
 
         if task_versions.is_empty() {
@@ -75,6 +92,31 @@ impl YamlConfig {
             ));
         }
 
-        Ok(YamlConfig { task_versions })
+        Ok(YamlConfig {
+            task_versions,
+            task_policies,
+        })
     }
 }
+
+fn parse_versions(task_name: &str, node: &YamlNode) -> Result<Vec<VersionRequirement>> {
+    let items = match &node.value {
+        YamlValue::Sequence(items) => items.iter().collect(),
+        YamlValue::Scalar(_) => vec![node],
+        _ => Vec::new(),
+    };
+
+    items
+        .into_iter()
+        .filter_map(|item| item.as_str())
+        .filter(|version| !version.trim().is_empty())
+        .map(|version| {
+            VersionRequirement::parse(version).map_err(|_| {
+                Error::Config(format!(
+                    "Invalid version requirement '{}' for task '{}'",
+                    version, task_name
+                ))
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,162 @@
+use ciprobe::azure_pipeline::{AzurePipeline, TemplateKind};
+
+fn task_names(content: &str) -> Vec<String> {
+    AzurePipeline::parse(content)
+        .unwrap()
+        .tasks()
+        .map(|t| format!("{}@{}", t.name, t.version))
+        .collect()
+}
+
+#[test]
+fn test_tasks_in_stages_jobs_and_steps() {
+    let content = r#"
+trigger:
+  - main
+
+stages:
+  - stage: Build
+    jobs:
+      - job: Compile
+        steps:
+          - task: UseNode@1
+            inputs:
+              version: '18.x'
+          - script: npm ci
+  - stage: Deploy
+    jobs:
+      - deployment: Release
+        strategy:
+          runOnce:
+            deploy:
+              steps:
+                - task: AzureWebApp@1
+            on:
+              failure:
+                steps:
+                  - task: Notify@2
+"#;
+
+    assert_eq!(
+        task_names(content),
+        vec!["UseNode@1", "AzureWebApp@1", "Notify@2"]
+    );
+}
+
+#[test]
+fn test_ignores_task_text_outside_steps() {
+    let content = r#"
+variables:
+  note: 'task: Fake@1'
+steps:
+  - script: |
+      echo "task: NotATask@1"
+      # task: AlsoNot@2
+  - bash: echo done # task: Comment@3
+  - task: Real@4 # task: Trailing@5
+"#;
+
+    assert_eq!(task_names(content), vec!["Real@4"]);
+}
+
+#[test]
+fn test_multiline_and_flow_style_tasks() {
+    let content = r#"
+steps:
+  - task:
+      MultiLine@2
+  - { task: FlowStyle@3, inputs: { a: b } }
+  - task: >-
+      Folded@4
+  - ${{ if eq(parameters.publish, true) }}:
+    - task: Conditional@5
+"#;
+
+    assert_eq!(
+        task_names(content),
+        vec!["MultiLine@2", "FlowStyle@3", "Folded@4", "Conditional@5"]
+    );
+}
+
+#[test]
+fn test_task_positions_and_templates() {
+    let content = "steps:\n  - template: steps/build.yml\n    parameters:\n      extra:\n        - task: Param@1\n  - task: UseNode@1\n";
+    let pipeline = AzurePipeline::parse(content).unwrap();
+
+    let templates: Vec<_> = pipeline.templates().collect();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].kind, TemplateKind::Step);
+    assert_eq!(templates[0].path, "steps/build.yml");
+
+    let tasks: Vec<_> = pipeline.tasks().collect();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].name, "Param");
+    assert_eq!((tasks[1].line, tasks[1].column), (6, 11));
+    assert_eq!(tasks[1].source, "- task: UseNode@1");
+}
+
+#[test]
+fn test_repository_resources() {
+    let content = r#"
+resources:
+  repositories:
+    - repository: templates
+      type: git
+      name: Shared/pipeline-templates
+      ref: refs/heads/main
+    - repository: local
+      type: git
+      name: tools
+    - repository: gh
+      type: github
+      name: org/actions
+      endpoint: github
+"#;
+    let pipeline = AzurePipeline::parse(content).unwrap();
+    let consumer = "https://dev.azure.com/org/App/_git/web";
+
+    let templates = pipeline.repository("templates").unwrap();
+    assert_eq!(templates.branch_or_ref(), Some("main"));
+    assert_eq!(
+        templates.repository_url(consumer).as_deref(),
+        Some("https://dev.azure.com/org/Shared/_git/pipeline-templates")
+    );
+    assert_eq!(
+        pipeline
+            .repository("local")
+            .unwrap()
+            .repository_url(consumer)
+            .as_deref(),
+        Some("https://dev.azure.com/org/App/_git/tools")
+    );
+    assert_eq!(
+        pipeline
+            .repository("gh")
+            .unwrap()
+            .repository_url(consumer)
+            .as_deref(),
+        Some("https://github.com/org/actions")
+    );
+}
+
+#[test]
+fn test_mismatched_flow_closers_are_errors() {
+    for content in ["steps: [a}\n", "x: {a]\n", "[}\n", "{]\n"] {
+        assert!(
+            ciprobe::yaml_document::parse_document(content).is_err(),
+            "{:?} should not parse",
+            content
+        );
+    }
+
+    let node = ciprobe::yaml_document::parse_document("words: [it's, \"a, b\"]\n").unwrap();
+    let words: Vec<_> = node
+        .get("words")
+        .unwrap()
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|w| w.as_str().unwrap())
+        .collect();
+    assert_eq!(words, ["it's", "a, b"]);
+}
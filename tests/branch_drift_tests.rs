@@ -0,0 +1,88 @@
+mod common;
+
+use ciprobe::report::generate_markdown_report;
+use ciprobe::{analyze_pipelines, expand_branches, AnalysisOptions, Config, RepoEntry};
+use common::git;
+use std::fs;
+use std::path::Path;
+
+fn commit_branch(work: &Path, branch: &str, version: &str) {
+    git(work, &["checkout", "-B", branch]);
+    fs::write(
+        work.join("azure-pipelines.yml"),
+        format!("steps:\n  - task: UseNode@{}\n  - task: Npm@1\n", version),
+    )
+    .unwrap();
+    git(work, &["add", "."]);
+    git(work, &["commit", "-m", branch]);
+    git(work, &["push", "origin", branch]);
+}
+
+#[test]
+fn test_branch_drift_matrix() {
+    let name = format!("ciprobe-drift-{}", std::process::id());
+    let root = std::env::temp_dir().join(&name);
+    let remote = root.join(format!("{}.git", name));
+    let work = root.join("work");
+    fs::create_dir_all(&remote).unwrap();
+    fs::create_dir_all(&work).unwrap();
+
+    git(&remote, &["init", "--bare"]);
+    git(&work, &["init"]);
+    git(
+        &work,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    commit_branch(&work, "main", "3");
+    commit_branch(&work, "release/1", "1");
+    commit_branch(&work, "release/2", "3");
+    commit_branch(&work, "feature/x", "2");
+    // Branch patterns are case-sensitive, like git
+    commit_branch(&work, "Release/3", "4");
+
+    let config =
+        Config::from_yaml("task_versions:\n  'usenode':\n    - '3'\n  'npm':\n    - '1'\n")
+            .unwrap();
+    let credentials = common::credentials();
+    let url = format!("file://{}", remote.display());
+    let patterns = ["main".to_string(), "release/*".to_string()];
+    let entries = expand_branches(
+        &[RepoEntry::new(&url)],
+        &patterns,
+        Some(&credentials),
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+    let repos: Vec<_> = entries.iter().map(RepoEntry::identity).collect();
+    assert_eq!(
+        repos,
+        [
+            format!("{}#main", url),
+            format!("{}#release/1", url),
+            format!("{}#release/2", url)
+        ]
+    );
+
+    let mut issues = analyze_pipelines(
+        &entries,
+        Some(&credentials),
+        &config,
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+    for entry in &entries {
+        issues.add_repo_metadata(entry);
+    }
+    assert!(issues.repos_failed.is_empty(), "{:?}", issues.repos_failed);
+    assert_eq!(issues.branch_groups()[url.as_str()].len(), 3);
+
+    let report = generate_markdown_report(&repos, &config, &issues).unwrap();
+    assert!(report.contains("## 🌿 Branch Drift"));
+    assert!(report.contains("| Task | main | release/1 | release/2 |"));
+    assert!(report.contains("| npm | 1 | 1 | 1 |"));
+    assert!(report.contains("| usenode | 3 | ⚠️ 1 | 3 |"));
+    assert!(report.contains("Outdated branches: `release/1`"));
+
+    fs::remove_dir_all(&root).unwrap();
+    common::remove_checkouts(&name);
+}
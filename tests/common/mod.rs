@@ -0,0 +1,66 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use ciprobe::{Credentials, TaskImplementation};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Runs git in `dir` and returns its output, failing the test on errors.
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "user.name=ciprobe",
+            "-c",
+            "user.email=ciprobe@example.com",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {:?}",
+        args,
+        output
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// A usage of version `version` on line 1 of `file_path`, included directly
+/// by the pipeline.
+pub fn implementation(repo_name: &str, version: &str, file_path: &str) -> TaskImplementation {
+    TaskImplementation {
+        repo_name: repo_name.to_string(),
+        version: version.to_string(),
+        file_path: PathBuf::from(file_path),
+        line: 1,
+        column: 1,
+        source: String::new(),
+        include_chain: vec![PathBuf::from(file_path)],
+        consumers: Vec::new(),
+    }
+}
+
+pub fn credentials() -> Credentials {
+    Credentials {
+        username: "user".to_string(),
+        token: "token".to_string(),
+    }
+}
+
+/// Removes the checkouts under `temp_repos` of repositories named `name`.
+pub fn remove_checkouts(name: &str) {
+    for entry in fs::read_dir("temp_repos").unwrap() {
+        let path = entry.unwrap().path();
+        if path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with(name)
+        {
+            fs::remove_dir_all(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use ciprobe::{analyze_pipelines, AnalysisOptions, Config, RepoEntry};
+use common::git;
+use std::fs;
+use std::path::Path;
+
+fn commit_pipeline(work: &Path, branch: &str, version: &str) {
+    git(work, &["checkout", "-B", branch]);
+    fs::write(
+        work.join("azure-pipelines.yml"),
+        format!("steps:\n  - task: UseNode@{}\n", version),
+    )
+    .unwrap();
+    git(work, &["add", "."]);
+    git(work, &["commit", "-m", version]);
+    git(work, &["push", "origin", branch]);
+}
+
+#[test]
+fn test_remote_head_decides_the_default_branch() {
+    let name = format!("ciprobe-head-{}", std::process::id());
+    let root = std::env::temp_dir().join(&name);
+    let remote = root.join(format!("{}.git", name));
+    let work = root.join("work");
+    fs::create_dir_all(&remote).unwrap();
+    fs::create_dir_all(&work).unwrap();
+
+    git(&remote, &["init", "--bare"]);
+    git(&work, &["init"]);
+    git(
+        &work,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    // A stale `develop` would win with the old guessing order
+    commit_pipeline(&work, "develop", "0");
+    commit_pipeline(&work, "trunk", "1");
+    git(&remote, &["symbolic-ref", "HEAD", "refs/heads/trunk"]);
+
+    let config = Config::from_yaml("task_versions:\n  'usenode':\n    - '1'\n").unwrap();
+    let credentials = common::credentials();
+    let url = format!("file://{}", remote.display());
+    let issues = analyze_pipelines(
+        &[RepoEntry::new(&url)],
+        Some(&credentials),
+        &config,
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+
+    assert!(issues.repos_failed.is_empty(), "{:?}", issues.repos_failed);
+    assert_eq!(issues.repo_branches[&url], "trunk");
+    assert_eq!(issues.all_implementations["usenode"][0].version, "1");
+    assert!(issues.invalid_states.is_empty());
+
+    fs::remove_dir_all(&root).unwrap();
+    common::remove_checkouts(&name);
+}
@@ -0,0 +1,198 @@
+use ciprobe::{AzureDevOpsDiscovery, Credentials, GitHubDiscovery, RepoFilter};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+/// Serves `bodies` as JSON responses to consecutive requests and returns the
+/// request lines and headers it received.
+fn serve(bodies: Vec<String>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let responses = bodies
+        .into_iter()
+        .map(|body| (String::new(), body))
+        .collect();
+    (base_url, respond(listener, responses))
+}
+
+/// Answers each request with the next `(extra headers, body)` response.
+fn respond(listener: TcpListener, responses: Vec<(String, String)>) -> JoinHandle<Vec<String>> {
+    thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (headers, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            requests.push(request);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                body.len(),
+                headers,
+                body
+            )
+            .unwrap();
+        }
+        requests
+    })
+}
+
+fn repository(project: &str, name: &str, extra: &str) -> String {
+    format!(
+        r#"{{"id": "{name}-id", "name": "{name}", "project": {{"name": "{project}"}},
+            "webUrl": "https://dev.azure.com/org/{project}/_git/{name}"{extra}}}"#
+    )
+}
+
+#[test]
+fn test_azure_devops_discovery() {
+    let body = format!(
+        r#"{{"count": 5, "value": [{}, {}, {}, {}, {}]}}"#,
+        repository("Web", "site", r#", "defaultBranch": "refs/heads/main""#),
+        repository("Api", "orders", r#", "defaultBranch": "refs/heads/main""#),
+        repository("Api", "empty", ""),
+        repository(
+            "Api",
+            "old",
+            r#", "defaultBranch": "refs/heads/main", "isDisabled": true"#
+        ),
+        repository(
+            "Api",
+            "orders-sandbox",
+            r#", "defaultBranch": "refs/heads/main""#
+        ),
+    );
+    let (base_url, server) = serve(vec![body]);
+
+    let credentials = Credentials {
+        username: "user".to_string(),
+        token: "secret".to_string(),
+    };
+    let repos = AzureDevOpsDiscovery::new("org")
+        .with_base_url(&base_url)
+        .with_filter(RepoFilter {
+            include: Vec::new(),
+            exclude: vec!["*-sandbox".to_string()],
+        })
+        .discover(&credentials, false)
+        .unwrap();
+
+    let urls: Vec<_> = repos.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://dev.azure.com/org/Api/_git/orders",
+            "https://dev.azure.com/org/Web/_git/site",
+        ]
+    );
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /org/_apis/git/repositories?api-version=7.1 "));
+    // Basic auth for user:secret
+    assert!(requests[0].contains("Authorization: Basic dXNlcjpzZWNyZXQ="));
+}
+
+#[test]
+fn test_azure_devops_discovery_per_project() {
+    let (base_url, server) = serve(vec![
+        format!(
+            r#"{{"value": [{}]}}"#,
+            repository(
+                "My Project",
+                "app",
+                r#", "defaultBranch": "refs/heads/main""#
+            )
+        ),
+        r#"{"value": []}"#.to_string(),
+    ]);
+
+    let credentials = Credentials {
+        username: "user".to_string(),
+        token: "secret".to_string(),
+    };
+    let repos = AzureDevOpsDiscovery::new("org")
+        .with_base_url(&base_url)
+        .with_projects(&["My Project".to_string(), "Other".to_string()])
+        .discover(&credentials, false)
+        .unwrap();
+    assert_eq!(repos.len(), 1);
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /org/My%20Project/_apis/git/repositories"));
+    assert!(requests[1].starts_with("GET /org/Other/_apis/git/repositories"));
+}
+
+#[test]
+fn test_repo_filter() {
+    let filter = RepoFilter {
+        include: vec!["Api/*".to_string()],
+        exclude: vec!["*-sandbox".to_string()],
+    };
+    assert!(filter.matches(&["orders", "Api/orders"]));
+    assert!(!filter.matches(&["orders-sandbox", "Api/orders-sandbox"]));
+    assert!(!filter.matches(&["site", "Web/site"]));
+}
+
+fn github_repository(name: &str, archived: bool, fork: bool, topics: &str) -> String {
+    format!(
+        r#"{{"name": "{name}", "full_name": "org/{name}", "html_url": "https://github.com/org/{name}",
+            "archived": {archived}, "fork": {fork}, "topics": {topics}}}"#
+    )
+}
+
+#[test]
+fn test_github_discovery_follows_pages() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let api_url = format!("http://{}/api/v3", listener.local_addr().unwrap());
+    let first_page = format!(
+        "[{}, {}, {}]",
+        github_repository("web", false, false, r#"["ci", "node"]"#),
+        github_repository("old", true, false, r#"["ci"]"#),
+        github_repository("docs", false, false, "[]"),
+    );
+    let second_page = format!(
+        "[{}, {}]",
+        github_repository("api", false, false, r#"["CI"]"#),
+        github_repository("api-fork", false, true, r#"["ci"]"#),
+    );
+    let server = respond(
+        listener,
+        vec![
+            (
+                format!(
+                    "Link: <{}/organizations/1/repos?page=2>; rel=\"next\", <{}/organizations/1/repos?page=2>; rel=\"last\"\r\n",
+                    api_url, api_url
+                ),
+                first_page,
+            ),
+            (String::new(), second_page),
+        ],
+    );
+
+    let repos = GitHubDiscovery::new("org")
+        .with_api_url(&api_url)
+        .with_token(Some("ghp_secret"))
+        .with_topics(&["ci".to_string()])
+        .discover(false)
+        .unwrap();
+
+    let urls: Vec<_> = repos.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec!["https://github.com/org/api", "https://github.com/org/web"]
+    );
+    assert_eq!(repos[1].tags, vec!["ci", "node"]);
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /api/v3/orgs/org/repos?type=all&per_page=100 "));
+    assert!(requests[0].contains("Authorization: Bearer ghp_secret"));
+    assert!(requests[1].starts_with("GET /api/v3/organizations/1/repos?page=2 "));
+}
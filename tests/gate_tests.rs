@@ -0,0 +1,113 @@
+mod common;
+
+use ciprobe::gate::{
+    error_exit_code, EXIT_ANALYSIS_ERROR, EXIT_SUCCESS, EXIT_USAGE_ERROR, EXIT_VIOLATIONS,
+};
+use ciprobe::{Cli, Error, Gate, GateCheck, TaskIssues};
+use common::implementation;
+
+fn issues_with_two_invalid() -> TaskIssues {
+    let mut issues = TaskIssues::default();
+    issues.add_invalid_state(
+        "usenode",
+        implementation("repo-a", "0", "azure-pipelines.yml"),
+    );
+    issues.add_invalid_state(
+        "usenode",
+        implementation("repo-b", "0", "azure-pipelines.yml"),
+    );
+    issues
+}
+
+fn cli(args: &[&str]) -> ciprobe::Result<Cli> {
+    Cli::parse_from(args.iter().map(|a| a.to_string()))
+}
+
+#[test]
+fn test_no_thresholds_always_pass() {
+    let issues = issues_with_two_invalid();
+    let violations = Gate::default().evaluate(&issues);
+
+    assert!(violations.is_empty());
+    assert_eq!(Gate::exit_code(&violations), EXIT_SUCCESS);
+}
+
+#[test]
+fn test_thresholds() {
+    let issues = issues_with_two_invalid();
+
+    let mut gate = Gate::default();
+    gate.max(GateCheck::Invalid, 2);
+    assert_eq!(Gate::exit_code(&gate.evaluate(&issues)), EXIT_SUCCESS);
+
+    gate.fail_on(GateCheck::Missing);
+    assert_eq!(Gate::exit_code(&gate.evaluate(&issues)), EXIT_SUCCESS);
+
+    // The strictest threshold wins
+    gate.max(GateCheck::Invalid, 1);
+    gate.max(GateCheck::Invalid, 5);
+    let violations = gate.evaluate(&issues);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].check, GateCheck::Invalid);
+    assert_eq!(violations[0].count, 2);
+    assert_eq!(Gate::exit_code(&violations), EXIT_VIOLATIONS);
+}
+
+#[test]
+fn test_analysis_errors_take_precedence() {
+    let mut issues = issues_with_two_invalid();
+    issues.add_failed_repo("repo-c", "Git error: clone failed");
+
+    let cli = cli(&["--repos", "a", "--fail-on", "invalid,error"]).unwrap();
+    let violations = cli.gate.evaluate(&issues);
+
+    assert_eq!(violations.len(), 2);
+    assert_eq!(Gate::exit_code(&violations), EXIT_ANALYSIS_ERROR);
+}
+
+#[test]
+fn test_cli_usage_errors() {
+    let unknown_check = cli(&["--repos", "a", "--fail-on", "warnings"]).unwrap_err();
+    assert_eq!(error_exit_code(&unknown_check), EXIT_USAGE_ERROR);
+
+    let not_a_number = cli(&["--repos", "a", "--max-invalid", "many"]).unwrap_err();
+    assert_eq!(error_exit_code(&not_a_number), EXIT_USAGE_ERROR);
+
+    assert_eq!(
+        error_exit_code(&Error::Git("clone failed".to_string())),
+        EXIT_ANALYSIS_ERROR
+    );
+}
+
+#[test]
+fn test_failed_repositories_fail_by_default() {
+    // Every repository failed, so nothing was analyzed
+    let mut issues = TaskIssues::default();
+    issues.add_failed_repo("repo-a", "Git error: clone failed");
+    issues.add_failed_repo("repo-b", "Git error: clone failed");
+
+    let default = cli(&["--repos", "a"]).unwrap();
+    let violations = default.gate.evaluate(&issues);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].count, 2);
+    assert_eq!(Gate::exit_code(&violations), EXIT_ANALYSIS_ERROR);
+
+    // Explicitly tolerated failures pass
+    let tolerant = cli(&["--repos", "a", "--max-errors", "2"]).unwrap();
+    assert_eq!(
+        Gate::exit_code(&tolerant.gate.evaluate(&issues)),
+        EXIT_SUCCESS
+    );
+
+    // Unparsed files alone only count with a threshold
+    let mut unparsed = TaskIssues::default();
+    unparsed.add_failed_file(
+        "repo-a",
+        std::path::Path::new("broken.yml"),
+        "Parse error: line 1: invalid flow collection",
+    );
+    assert_eq!(
+        Gate::exit_code(&default.gate.evaluate(&unparsed)),
+        EXIT_SUCCESS
+    );
+}
@@ -0,0 +1,103 @@
+use ciprobe::github_actions::{ActionKind, GitHubWorkflow};
+use ciprobe::line_parser::{parse_action_reference, TaskDefinition};
+use ciprobe::{detect_platform, Platform};
+use std::path::Path;
+
+#[test]
+fn test_parse_action_reference() {
+    assert_eq!(
+        parse_action_reference("actions/checkout@v4"),
+        Some(TaskDefinition {
+            name: "actions/checkout".to_string(),
+            version: "v4".to_string(),
+        })
+    );
+    assert_eq!(
+        parse_action_reference("github/codeql-action/init@3.2.1"),
+        Some(TaskDefinition {
+            name: "github/codeql-action/init".to_string(),
+            version: "3.2.1".to_string(),
+        })
+    );
+
+    assert_eq!(parse_action_reference("./.github/actions/setup"), None);
+    assert_eq!(parse_action_reference("docker://alpine:3.19"), None);
+    assert_eq!(parse_action_reference("actions/checkout"), None);
+    assert_eq!(parse_action_reference("checkout@v4"), None);
+}
+
+#[test]
+fn test_workflow_steps_and_reusable_workflows() {
+    let content = r#"
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - name: Setup
+        uses: actions/setup-node@v3
+        with:
+          node-version: 20
+      - run: echo "uses: fake/action@v1"
+      - uses: ./.github/actions/local
+  deploy:
+    uses: org/shared/.github/workflows/deploy.yml@main
+"#;
+
+    let workflow = GitHubWorkflow::parse(content).unwrap();
+    let found: Vec<_> = workflow
+        .actions
+        .iter()
+        .map(|a| (a.kind, format!("{}@{}", a.task.name, a.task.version)))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (ActionKind::Step, "actions/checkout@v4".to_string()),
+            (ActionKind::Step, "actions/setup-node@v3".to_string()),
+            (
+                ActionKind::ReusableWorkflow,
+                "org/shared/.github/workflows/deploy.yml@main".to_string()
+            ),
+        ]
+    );
+
+    let setup_node = &workflow.actions[1].task;
+    assert_eq!((setup_node.line, setup_node.column), (9, 15));
+    assert_eq!(setup_node.source, "uses: actions/setup-node@v3");
+}
+
+#[test]
+fn test_composite_action() {
+    let content = r#"
+name: Setup
+runs:
+  using: composite
+  steps:
+    - uses: actions/cache@v4
+    - run: npm ci
+      shell: bash
+"#;
+
+    let workflow = GitHubWorkflow::parse(content).unwrap();
+    let names: Vec<_> = workflow.tasks().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["actions/cache"]);
+}
+
+#[test]
+fn test_detect_platform() {
+    assert_eq!(
+        detect_platform(Path::new(".github/workflows/ci.yml")),
+        Platform::GitHubActions
+    );
+    assert_eq!(
+        detect_platform(Path::new(".github/actions/setup/action.yml")),
+        Platform::GitHubActions
+    );
+    assert_eq!(
+        detect_platform(Path::new("azure-pipelines.yml")),
+        Platform::AzurePipelines
+    );
+}
@@ -0,0 +1,105 @@
+use ciprobe::gitlab_ci::{GitLabPipeline, GitLabReferenceKind};
+use ciprobe::line_parser::{parse_component_reference, parse_image_reference, TaskDefinition};
+
+fn definition(name: &str, version: &str) -> Option<TaskDefinition> {
+    Some(TaskDefinition {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+#[test]
+fn test_parse_image_reference() {
+    assert_eq!(
+        parse_image_reference("node:18-alpine"),
+        definition("node", "18-alpine")
+    );
+    assert_eq!(
+        parse_image_reference("registry.example.com:5000/team/app:1.2"),
+        definition("registry.example.com:5000/team/app", "1.2")
+    );
+    assert_eq!(
+        parse_image_reference("alpine@sha256:abc123"),
+        definition("alpine", "sha256:abc123")
+    );
+    assert_eq!(
+        parse_image_reference("ubuntu"),
+        definition("ubuntu", "latest")
+    );
+    assert_eq!(parse_image_reference("$BUILD_IMAGE"), None);
+}
+
+#[test]
+fn test_parse_component_reference() {
+    assert_eq!(
+        parse_component_reference("$CI_SERVER_FQDN/my-org/security/secret-detection@1.0"),
+        definition("my-org/security/secret-detection", "1.0")
+    );
+    assert_eq!(
+        parse_component_reference("gitlab.com/components/sast@~latest"),
+        definition("components/sast", "~latest")
+    );
+    assert_eq!(parse_component_reference("components/sast"), None);
+}
+
+#[test]
+fn test_gitlab_pipeline_references() {
+    let content = r#"
+include:
+  - local: /ci/local.yml
+  - project: 'platform/ci-templates'
+    ref: v2.1
+    file: '/templates/build.yml'
+  - project: platform/unpinned
+    file: deploy.yml
+  - component: $CI_SERVER_FQDN/platform/components/lint@1.4
+  - template: Auto-DevOps.gitlab-ci.yml
+
+image: python:3.12
+
+default:
+  image:
+    name: node:20
+
+.base: &base
+  image: alpine:3.19
+
+test:
+  <<: *base
+  script:
+    - echo "image: fake:1"
+
+build:
+  stage: build
+  script: make
+"#;
+
+    let pipeline = GitLabPipeline::parse(content).unwrap();
+    let found: Vec<_> = pipeline
+        .references
+        .iter()
+        .map(|r| (r.kind, format!("{}@{}", r.task.name, r.task.version)))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (
+                GitLabReferenceKind::ProjectInclude,
+                "platform/ci-templates@v2.1".to_string()
+            ),
+            (
+                GitLabReferenceKind::ProjectInclude,
+                "platform/unpinned@HEAD".to_string()
+            ),
+            (
+                GitLabReferenceKind::Component,
+                "platform/components/lint@1.4".to_string()
+            ),
+            (GitLabReferenceKind::Image, "python@3.12".to_string()),
+            (GitLabReferenceKind::Image, "node@20".to_string()),
+            (GitLabReferenceKind::Image, "alpine@3.19".to_string()),
+            (GitLabReferenceKind::Image, "alpine@3.19".to_string()),
+        ]
+    );
+}
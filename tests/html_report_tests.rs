@@ -0,0 +1,54 @@
+mod common;
+
+use ciprobe::html_report::generate_html_report;
+use ciprobe::{Config, TaskImplementation, TaskIssues};
+use common::implementation;
+use std::path::PathBuf;
+
+#[test]
+fn test_html_report_is_self_contained() {
+    let config = Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'copyfiles':\n    policy: required\n",
+    )
+    .unwrap();
+
+    let repos = vec![
+        "https://dev.azure.com/org/project/_git/repo-a".to_string(),
+        "https://dev.azure.com/org/project/_git/repo-b".to_string(),
+    ];
+    let mut issues = TaskIssues::default();
+    issues.repos_analyzed.insert(repos[0].clone());
+    issues.add_failed_repo(&repos[1], "Git error: clone failed");
+    issues.add_missing_task(&repos[0], "copyfiles");
+    issues.add_implementation(
+        "UseNode",
+        TaskImplementation {
+            include_chain: vec![
+                PathBuf::from("azure-pipelines.yml"),
+                PathBuf::from("templates/<build>.yml"),
+            ],
+            ..implementation(&repos[0], "0", "templates/<build>.yml")
+        },
+        &config,
+        false,
+    );
+
+    let html = generate_html_report(&repos, &config, &issues).unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(!html.contains("<script src"));
+    assert!(!html.contains("<link"));
+    assert!(html.contains("<style>"));
+
+    // Matrix row for repo-a: copyfiles missing, usenode invalid
+    assert!(html.contains(
+        "<td><span class=\"status missing\">missing</span></td><td><span class=\"status invalid\" title=\"invalid\">0</span></td>"
+    ));
+    assert!(html.contains("<span class=\"status failed\" title=\"Git error: clone failed\">"));
+
+    // Implementation details are escaped and collapsible
+    assert!(html.contains(
+        "<details><summary><code><a href=\"https://dev.azure.com/org/project/_git/repo-a?path=/templates/&lt;build&gt;.yml&amp;line=1&amp;lineEnd=2&amp;lineStartColumn=1&amp;lineEndColumn=1\">templates/&lt;build&gt;.yml:1:1</a></code></summary>"
+    ));
+    assert!(html.contains("data-task=\"usenode\" data-version=\"0\" data-status=\"invalid\""));
+}
@@ -0,0 +1,100 @@
+use ciprobe::glob::glob_match;
+use ciprobe::{Cli, Config, Inventory, RepoEntry, TaskIssues};
+
+#[test]
+fn test_plain_text_inventory() {
+    let inventory = Inventory::parse_text(
+        "# Platform repositories\n\
+         https://dev.azure.com/org/project/_git/api\n\
+         \n\
+         https://github.com/org/web#release/2.0  # pinned for the audit\n",
+    );
+    let identities: Vec<_> = inventory
+        .entries()
+        .iter()
+        .map(RepoEntry::identity)
+        .collect();
+    assert_eq!(
+        identities,
+        vec![
+            "https://dev.azure.com/org/project/_git/api",
+            "https://github.com/org/web#release/2.0",
+        ]
+    );
+}
+
+#[test]
+fn test_yaml_and_json_inventories() {
+    let yaml = Inventory::parse_manifest(
+        "repositories:\n\
+         \x20 - https://github.com/org/api\n\
+         \x20 - url: https://github.com/org/web\n\
+         \x20   branch: main\n\
+         \x20   team: frontend\n\
+         \x20   tags: [node, public]\n\
+         \x20 - url: https://github.com/org/legacy\n\
+         \x20   exclude: true\n\
+         \x20 - https://github.com/org/tools-archive\n\
+         exclude:\n\
+         \x20 - '*-archive'\n",
+    )
+    .unwrap();
+    let entries = yaml.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].identity(), "https://github.com/org/web#main");
+    assert_eq!(entries[1].team.as_deref(), Some("frontend"));
+    assert_eq!(entries[1].tags, vec!["node", "public"]);
+
+    let json = Inventory::parse_manifest(
+        r#"{
+  "repositories": [
+    {"url": "https://github.com/org/web", "team": "frontend", "tags": ["node"]},
+    "https://github.com/org/api"
+  ]
+}"#,
+    )
+    .unwrap();
+    assert_eq!(json.entries().len(), 2);
+    assert_eq!(json.entries()[0].team.as_deref(), Some("frontend"));
+
+    assert!(Inventory::parse_manifest("repositories:\n  - team: x\n").is_err());
+}
+
+#[test]
+fn test_repos_file_is_an_alternative_to_repos() {
+    let cli = Cli::parse_from(["--repos-file", "repos.txt"].map(String::from)).unwrap();
+    assert_eq!(
+        cli.repos_file.as_deref(),
+        Some(std::path::Path::new("repos.txt"))
+    );
+    assert!(Cli::parse_from(Vec::<String>::new()).is_err());
+
+    assert!(glob_match("*-Archive", "tools-archive"));
+    assert!(glob_match(
+        "https://github.com/org/*",
+        "https://github.com/org/web"
+    ));
+    assert!(!glob_match("web?", "web"));
+}
+
+#[test]
+fn test_report_groups_by_team() {
+    let config =
+        Config::from_yaml("task_versions:\n  'copyfiles':\n    policy: required\n").unwrap();
+    let mut entry = RepoEntry::new("https://github.com/org/web");
+    entry.team = Some("frontend".to_string());
+
+    let mut issues = TaskIssues::default();
+    issues.repos_analyzed.insert(entry.identity());
+    issues.add_missing_task(&entry.identity(), "copyfiles");
+    issues.add_repo_metadata(&entry);
+
+    let md =
+        ciprobe::report::generate_markdown_report(&[entry.identity()], &config, &issues).unwrap();
+    assert!(md.contains("## 👥 Teams"));
+    assert!(md.contains("| frontend | 1 | 0 | 1 | 0 |"));
+
+    let json =
+        ciprobe::json_report::generate_json_report(&[entry.identity()], &config, &issues).unwrap();
+    assert!(json.contains("\"team\": \"frontend\""));
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use ciprobe::json::JsonValue;
+use ciprobe::json_report::generate_json_report;
+use ciprobe::report::format_utc_timestamp;
+use ciprobe::{Config, TaskImplementation, TaskIssues};
+use common::implementation;
+use std::path::PathBuf;
+
+#[test]
+fn test_json_value_serialization() {
+    let value = JsonValue::object([
+        ("name", "quote \" and \\ and\nnewline".into()),
+        ("count", 3usize.into()),
+        ("empty", JsonValue::array(Vec::<String>::new())),
+        ("missing", Option::<String>::None.into()),
+    ]);
+
+    assert_eq!(
+        value.to_string(),
+        r#"{"name":"quote \" and \\ and\nnewline","count":3,"empty":[],"missing":null}"#
+    );
+    assert_eq!(
+        JsonValue::array(["a"]).to_pretty_string(),
+        "[\n  \"a\"\n]\n"
+    );
+}
+
+#[test]
+fn test_format_utc_timestamp() {
+    assert_eq!(format_utc_timestamp(0), "1970-01-01T00:00:00Z");
+    assert_eq!(format_utc_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(format_utc_timestamp(1_735_689_599), "2024-12-31T23:59:59Z");
+}
+
+#[test]
+fn test_json_report_contents() {
+    let config = Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'copyfiles':\n    policy: required\n",
+    )
+    .unwrap();
+
+    let repos = vec![
+        "https://dev.azure.com/org/project/_git/repo-a".to_string(),
+        "https://dev.azure.com/org/project/_git/repo-b".to_string(),
+    ];
+    let mut issues = TaskIssues::default();
+    issues.repos_analyzed.insert(repos[0].clone());
+    issues.add_failed_repo(&repos[1], "Git error: clone failed");
+    issues.add_missing_task(&repos[0], "copyfiles");
+    issues.add_implementation(
+        "UseNode",
+        TaskImplementation {
+            include_chain: vec![
+                PathBuf::from("azure-pipelines.yml"),
+                PathBuf::from("templates/build.yml"),
+            ],
+            ..implementation(&repos[0], "0", "templates/build.yml")
+        },
+        &config,
+        false,
+    );
+
+    let json = generate_json_report(&repos, &config, &issues).unwrap();
+
+    assert!(json.starts_with("{\n  \"schema_version\": 1,\n"));
+    assert!(json.contains("\"status\": \"failed\",\n      \"error\": \"Git error: clone failed\""));
+    assert!(json.contains("\"task\": \"usenode\",\n      \"status\": \"invalid\""));
+    assert!(json
+        .contains("\"task\": \"usenode\",\n      \"allowed_versions\": [\n        \"1\"\n      ]"));
+    assert!(json.contains("\"file\": \"templates/build.yml\""));
+    assert!(json.contains("\"tasks\": [\n        \"copyfiles\"\n      ]"));
+    assert!(json.contains("\"missing_required\": 1,"));
+    assert!(json.contains("\"unknown_tasks\": []"));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use ciprobe::junit_report::generate_junit_report;
+use ciprobe::{Config, TaskIssues};
+use common::implementation;
+
+#[test]
+fn test_junit_suites_and_cases() {
+    let config = Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'copyfiles':\n    policy: required\n",
+    )
+    .unwrap();
+
+    let repos: Vec<String> = ["repo-a", "repo-b", "repo-c", "repo-d"]
+        .iter()
+        .map(|r| format!("https://dev.azure.com/org/project/_git/{}", r))
+        .collect();
+    let mut issues = TaskIssues::default();
+    for repo in &repos[..3] {
+        issues.repos_analyzed.insert(repo.clone());
+    }
+    issues.repos_skipped.insert(repos[2].clone());
+    issues.add_failed_repo(&repos[3], "Git error: <clone failed>");
+
+    // repo-a is compliant, repo-b uses an invalid version and lacks copyfiles
+    issues.add_implementation(
+        "UseNode",
+        implementation(&repos[0], "1", "azure-pipelines.yml"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "CopyFiles",
+        implementation(&repos[0], "2", "azure-pipelines.yml"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "UseNode",
+        implementation(&repos[1], "0", "azure-pipelines.yml"),
+        &config,
+        false,
+    );
+    issues.add_missing_task(&repos[1], "copyfiles");
+
+    let xml = generate_junit_report(&repos, &config, &issues).unwrap();
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains(
+        "<testsuites name=\"ciprobe\" tests=\"8\" failures=\"2\" errors=\"2\" skipped=\"2\""
+    ));
+    assert_eq!(xml.matches("<testsuite ").count(), 4);
+    assert!(xml.contains(
+        "<testcase name=\"usenode\" classname=\"https://dev.azure.com/org/project/_git/repo-a\" time=\"0\"/>"
+    ));
+    assert!(xml.contains(
+        "<failure message=\"Task &apos;usenode&apos; has an invalid version (expected 1)\" type=\"ciprobe.violation\">Found version 0 in azure-pipelines.yml:1:1</failure>"
+    ));
+    assert!(xml.contains("<failure message=\"Required task &apos;copyfiles&apos; is missing\""));
+    assert!(xml.contains("<skipped message=\"No pipeline files found\"/>"));
+    assert!(xml.contains("<error message=\"Git error: &lt;clone failed&gt;\""));
+}
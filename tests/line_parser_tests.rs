@@ -1,4 +1,5 @@
 use ciprobe::line_parser::{parse_task_definition, TaskDefinition};
+use ciprobe::VersionKind;
 
 #[test]
 fn test_parse_task_definition() {
@@ -24,7 +25,7 @@ fn test_parse_task_definition() {
     assert_eq!(parse_task_definition("task:"), None);
     assert_eq!(parse_task_definition("task: @1"), None);
     assert_eq!(parse_task_definition("task: name@"), None);
-    assert_eq!(parse_task_definition("task: name@abc"), None);
+    assert_eq!(parse_task_definition("task: name@ab!c"), None);
     assert_eq!(parse_task_definition("other: name@123"), None);
     assert_eq!(parse_task_definition("task: invalid!name@123"), None);
 }
@@ -66,3 +67,25 @@ fn test_indented_tasks() {
         })
     );
 }
+
+#[test]
+fn test_non_numeric_versions() {
+    let cases = [
+        ("task: Foo@1.2.3", "1.2.3", VersionKind::Semver),
+        ("task: Foo@v4", "v4", VersionKind::Major),
+        ("task: Foo@2", "2", VersionKind::Major),
+        ("task: Foo@main", "main", VersionKind::Branch),
+        ("task: Foo@release/1.x", "release/1.x", VersionKind::Branch),
+        (
+            "task: Foo@8e5e7e5ab8b370d6c329ec480221332ada57f0ab",
+            "8e5e7e5ab8b370d6c329ec480221332ada57f0ab",
+            VersionKind::Commit,
+        ),
+    ];
+
+    for (line, version, kind) in cases {
+        let task = parse_task_definition(line).unwrap();
+        assert_eq!(task.version, version, "{}", line);
+        assert_eq!(task.version_kind(), kind, "{}", line);
+    }
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use ciprobe::{
+    analyze_pipelines, find_pipeline_files, AnalysisOptions, Cli, Config, RepoEntry, RepositoryUrl,
+};
+use common::git;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn config() -> Config {
+    Config::from_yaml("task_versions:\n  'usenode':\n    - '1'\n").unwrap()
+}
+
+#[test]
+fn test_local_checkout_is_analyzed_in_place() {
+    let dir = std::env::temp_dir().join(format!("ciprobe-local-{}", std::process::id()));
+    fs::create_dir_all(dir.join(".git")).unwrap();
+    fs::write(
+        dir.join("azure-pipelines.yml"),
+        "steps:\n  - task: UseNode@0\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join(".git").join("ignored.yml"),
+        "steps:\n  - task: UseNode@1\n",
+    )
+    .unwrap();
+
+    let entry = RepoEntry::local(&dir).unwrap();
+    let issues = analyze_pipelines(
+        std::slice::from_ref(&entry),
+        None,
+        &config(),
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+
+    let implementations = &issues.all_implementations["usenode"];
+    assert_eq!(implementations.len(), 1);
+    assert_eq!(implementations[0].repo_name, entry.identity());
+    assert_eq!(
+        implementations[0].file_path,
+        PathBuf::from("azure-pipelines.yml")
+    );
+    assert!(issues.repos_failed.is_empty());
+
+    let url = RepositoryUrl::parse(&entry.identity());
+    assert_eq!(url.name, dir.file_name().unwrap().to_string_lossy());
+    assert!(issues.file_url(&implementations[0]).is_none());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_remote_repositories_need_credentials() {
+    let entry = RepoEntry::new("https://github.com/org/app");
+    let issues = analyze_pipelines(&[entry], None, &config(), &AnalysisOptions::default()).unwrap();
+    assert!(issues.repos_failed["https://github.com/org/app"].contains("Credentials not found"));
+
+    assert!(RepoEntry::local(&PathBuf::from("/does/not/exist")).is_err());
+
+    let cli = Cli::parse_from(["--path", ".", "--path", "../other"].map(String::from)).unwrap();
+    assert_eq!(
+        cli.paths,
+        vec![PathBuf::from("."), PathBuf::from("../other")]
+    );
+}
+
+#[test]
+fn test_unparsed_files_are_recorded_as_errors() {
+    let dir = std::env::temp_dir().join(format!("ciprobe-unparsed-{}", std::process::id()));
+    fs::create_dir_all(dir.join(".github").join("workflows")).unwrap();
+    fs::write(
+        dir.join("azure-pipelines.yml"),
+        "steps:\n  - task: UseNode@1\n",
+    )
+    .unwrap();
+    fs::write(dir.join("broken.yml"), "steps: [{ task: Docker@2 ]\n").unwrap();
+    fs::write(
+        dir.join(".github").join("workflows").join("ci.yml"),
+        "jobs: {build]\n",
+    )
+    .unwrap();
+
+    let entry = RepoEntry::local(&dir).unwrap();
+    let issues = analyze_pipelines(
+        std::slice::from_ref(&entry),
+        None,
+        &config(),
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+
+    let mut failed: Vec<_> = issues.files_failed[&entry.identity()]
+        .iter()
+        .map(|(file, _)| file.clone())
+        .collect();
+    failed.sort();
+    assert_eq!(
+        failed,
+        vec![
+            PathBuf::from(".github/workflows/ci.yml"),
+            PathBuf::from("broken.yml")
+        ]
+    );
+    assert!(issues.repos_failed.is_empty());
+
+    let cli = Cli::parse_from(["--path", ".", "--fail-on", "error"].map(String::from)).unwrap();
+    let violations = cli.gate.evaluate(&issues);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].count, 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn write(dir: &Path, file: &str, content: &str) {
+    let path = dir.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_nested_checkouts_and_dependencies_are_not_searched() {
+    let root = std::env::temp_dir().join(format!("ciprobe-nested-{}", std::process::id()));
+    let plain = root.join("plain");
+    let work_tree = root.join("work-tree");
+
+    for dir in [&plain, &work_tree] {
+        write(dir, "azure-pipelines.yml", "steps:\n  - task: UseNode@1\n");
+        write(dir, ".github/workflows/ci.yml", "jobs: {}\n");
+        write(dir, "node_modules/pkg/azure-pipelines.yml", "steps: []\n");
+        write(dir, ".cache/build.yml", "steps: []\n");
+        // A checkout made by running ciprobe from inside the repository
+        let checkout = dir.join("temp_repos").join("other-0123456789abcdef");
+        write(&checkout, "azure-pipelines.yml", "steps: []\n");
+        git(&checkout, &["init", "-q"]);
+    }
+    git(&work_tree, &["init", "-q"]);
+    write(&work_tree, ".gitignore", "node_modules/\n.cache/\n");
+
+    for dir in [&plain, &work_tree] {
+        let files: Vec<_> = find_pipeline_files(dir, false)
+            .unwrap()
+            .into_iter()
+            .map(|file| file.strip_prefix(dir).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from(".github/workflows/ci.yml"),
+                PathBuf::from("azure-pipelines.yml")
+            ],
+            "{}",
+            dir.display()
+        );
+    }
+
+    fs::remove_dir_all(&root).unwrap();
+}
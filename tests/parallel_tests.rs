@@ -0,0 +1,143 @@
+mod common;
+
+use ciprobe::{analyze_pipelines, console, AnalysisOptions, Config, RepoEntry, TaskIssues};
+use common::git;
+use std::fs;
+
+fn config() -> Config {
+    Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'copyfiles':\n    policy: required\n",
+    )
+    .unwrap()
+}
+
+fn summary(issues: &TaskIssues) -> Vec<(String, String, String)> {
+    let mut rows: Vec<_> = issues.all_implementations["usenode"]
+        .iter()
+        .map(|i| {
+            (
+                i.repo_name.clone(),
+                i.version.clone(),
+                i.file_path.display().to_string(),
+            )
+        })
+        .collect();
+    rows.sort();
+    rows
+}
+
+#[test]
+fn test_parallel_analysis_matches_sequential() {
+    let root = std::env::temp_dir().join(format!("ciprobe-parallel-{}", std::process::id()));
+    let mut entries = Vec::new();
+    for i in 0..6 {
+        let dir = root.join(format!("repo-{}", i));
+        fs::create_dir_all(&dir).unwrap();
+        let mut pipeline = format!("steps:\n  - task: UseNode@{}\n", i % 2);
+        if i % 3 == 0 {
+            pipeline.push_str("  - task: CopyFiles@2\n");
+        }
+        fs::write(dir.join("azure-pipelines.yml"), pipeline).unwrap();
+        entries.push(RepoEntry::local(&dir).unwrap());
+    }
+    // An empty repository and one that cannot be fetched
+    fs::create_dir_all(root.join("empty")).unwrap();
+    entries.push(RepoEntry::local(&root.join("empty")).unwrap());
+    entries.push(RepoEntry::new("https://github.com/org/unreachable"));
+
+    let sequential =
+        analyze_pipelines(&entries, None, &config(), &AnalysisOptions::default()).unwrap();
+    let parallel = analyze_pipelines(
+        &entries,
+        None,
+        &config(),
+        &AnalysisOptions {
+            jobs: 4,
+            ..AnalysisOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(summary(&parallel), summary(&sequential));
+    assert_eq!(parallel.repos_analyzed, sequential.repos_analyzed);
+    assert_eq!(parallel.repos_skipped.len(), 1);
+    assert_eq!(parallel.repos_failed.len(), 1);
+    // repo-1, -2, -4, -5 and the empty repository lack the required task
+    assert_eq!(parallel.missing_required_tasks.len(), 5);
+    assert_eq!(
+        parallel.invalid_states["usenode"].len(),
+        sequential.invalid_states["usenode"].len()
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_console_output_is_captured_per_thread() {
+    let (value, output) = console::capture(|| {
+        ciprobe::console!("📂 Analyzing {}", "app");
+        ciprobe::console!("   └─ Found {} pipeline files", 2);
+        42
+    });
+    assert_eq!(value, 42);
+    assert_eq!(output, "📂 Analyzing app\n   └─ Found 2 pipeline files\n");
+
+    let cli = ciprobe::Cli::parse_from(["--repos", "a", "-j", "8"].map(String::from)).unwrap();
+    assert_eq!(cli.jobs, 8);
+    assert!(ciprobe::Cli::parse_from(["--repos", "a", "--jobs", "0"].map(String::from)).is_err());
+}
+
+#[test]
+fn test_parallel_clones_of_repositories_with_the_same_name() {
+    let name = format!("ciprobe-twin-{}", std::process::id());
+    let root = std::env::temp_dir().join(&name);
+    let mut entries = Vec::new();
+    for (org, version) in [("org-a", "0"), ("org-b", "1")] {
+        let remote = root.join(org).join(format!("{}.git", name));
+        let work = root.join(org).join("work");
+        fs::create_dir_all(&remote).unwrap();
+        fs::create_dir_all(&work).unwrap();
+        git(&remote, &["init", "--bare"]);
+        git(&work, &["init", "-b", "main"]);
+        git(
+            &work,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        fs::write(
+            work.join("azure-pipelines.yml"),
+            format!("steps:\n  - task: UseNode@{}\n", version),
+        )
+        .unwrap();
+        git(&work, &["add", "."]);
+        git(&work, &["commit", "-m", org]);
+        git(&work, &["push", "origin", "main"]);
+        entries.push(RepoEntry::new(&format!("file://{}", remote.display())));
+    }
+
+    let credentials = common::credentials();
+    let issues = analyze_pipelines(
+        &entries,
+        Some(&credentials),
+        &config(),
+        &AnalysisOptions {
+            jobs: 2,
+            ..AnalysisOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(issues.repos_failed.is_empty(), "{:?}", issues.repos_failed);
+    let versions: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            issues.all_implementations["usenode"]
+                .iter()
+                .find(|i| i.repo_name == entry.url)
+                .map(|i| i.version.as_str())
+        })
+        .collect();
+    assert_eq!(versions, [Some("0"), Some("1")]);
+
+    fs::remove_dir_all(&root).unwrap();
+    common::remove_checkouts(&name);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use ciprobe::json_report::generate_json_report;
+use ciprobe::{analyze_pipelines, AnalysisOptions, Config, RepoEntry};
+use common::git;
+use std::fs;
+use std::path::Path;
+
+fn commit_pipeline(work: &Path, version: &str) -> String {
+    fs::write(
+        work.join("azure-pipelines.yml"),
+        format!("steps:\n  - task: UseNode@{}\n", version),
+    )
+    .unwrap();
+    git(work, &["add", "."]);
+    git(work, &["commit", "-m", version]);
+    git(work, &["rev-parse", "HEAD"])
+}
+
+#[test]
+fn test_branch_tag_and_commit_refs() {
+    let name = format!("ciprobe-refs-{}", std::process::id());
+    let root = std::env::temp_dir().join(&name);
+    let remote = root.join(format!("{}.git", name));
+    let work = root.join("work");
+    fs::create_dir_all(&remote).unwrap();
+    fs::create_dir_all(&work).unwrap();
+
+    git(&remote, &["init", "--bare"]);
+    git(&work, &["init", "-b", "main"]);
+    git(
+        &work,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    let pinned = commit_pipeline(&work, "1");
+    git(&work, &["tag", "-a", "v1.0", "-m", "v1.0"]);
+    commit_pipeline(&work, "2");
+    git(&work, &["checkout", "-b", "release/3"]);
+    let release = commit_pipeline(&work, "3");
+    git(&work, &["push", "origin", "main", "release/3", "v1.0"]);
+
+    let config = Config::from_yaml("task_versions:\n  'usenode':\n    - '2'\n").unwrap();
+    let credentials = common::credentials();
+    let url = format!("file://{}", remote.display());
+    let repos = [
+        format!("{}#release/3", url),
+        format!("{}#v1.0", url),
+        format!("{}#{}", url, pinned),
+    ];
+    let entries: Vec<_> = repos.iter().map(|repo| RepoEntry::new(repo)).collect();
+    let issues = analyze_pipelines(
+        &entries,
+        Some(&credentials),
+        &config,
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+
+    assert!(issues.repos_failed.is_empty(), "{:?}", issues.repos_failed);
+
+    let version = |repo: &str| {
+        issues.all_implementations["usenode"]
+            .iter()
+            .find(|i| i.repo_name == repo)
+            .map(|i| i.version.clone())
+    };
+    assert_eq!(version(&repos[0]).as_deref(), Some("3"));
+    assert_eq!(version(&repos[1]).as_deref(), Some("1"));
+    assert_eq!(version(&repos[2]).as_deref(), Some("1"));
+    assert_eq!(issues.repo_commits[&repos[0]], release);
+    assert_eq!(issues.repo_commits[&repos[1]], pinned);
+    assert_eq!(issues.repo_commits[&repos[2]], pinned);
+    assert_eq!(issues.repo_branches[&repos[1]], "v1.0");
+
+    let report = generate_json_report(&repos, &config, &issues).unwrap();
+    assert!(report.contains(&format!("\"commit\": \"{}\"", release)));
+
+    // git's own message explains why a ref cannot be fetched
+    let missing = format!("{}#no-such-branch", url);
+    let failed = analyze_pipelines(
+        &[RepoEntry::new(&missing)],
+        Some(&credentials),
+        &config,
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+    let error = &failed.repos_failed[&missing];
+    assert!(
+        error.contains("Failed to fetch no-such-branch"),
+        "{}",
+        error
+    );
+    assert!(error.contains("remote ref"), "{}", error);
+
+    fs::remove_dir_all(&root).unwrap();
+    common::remove_checkouts(&name);
+}
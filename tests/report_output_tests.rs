@@ -0,0 +1,125 @@
+mod common;
+
+use ciprobe::report::{find_renderer, ReportOutput, RENDERERS};
+use ciprobe::{Cli, Config, TaskIssues};
+use common::implementation;
+use std::path::PathBuf;
+
+fn cli(args: &[&str]) -> ciprobe::Result<Cli> {
+    Cli::parse_from(args.iter().map(|a| a.to_string()))
+}
+
+fn outputs(cli: &Cli) -> Vec<(&'static str, PathBuf)> {
+    cli.report_outputs()
+        .into_iter()
+        .map(|o| (o.renderer.name(), o.path))
+        .collect()
+}
+
+#[test]
+fn test_report_outputs_from_cli() {
+    let default = cli(&["--repos", "a"]).unwrap();
+    assert_eq!(
+        outputs(&default),
+        vec![("markdown", PathBuf::from("report.md"))]
+    );
+
+    let only_reports = cli(&[
+        "--repos",
+        "a",
+        "--report",
+        "json=out/report.json",
+        "--report",
+        "sarif",
+    ])
+    .unwrap();
+    assert_eq!(
+        outputs(&only_reports),
+        vec![
+            ("json", PathBuf::from("out/report.json")),
+            ("sarif", PathBuf::from("report.sarif")),
+        ]
+    );
+
+    let combined = cli(&["--repos", "a", "--format", "md", "--report", "html=r.html"]).unwrap();
+    assert_eq!(
+        outputs(&combined),
+        vec![
+            ("markdown", PathBuf::from("report.md")),
+            ("html", PathBuf::from("r.html")),
+        ]
+    );
+}
+
+#[test]
+fn test_report_output_errors() {
+    assert!(cli(&["--repos", "a", "--report", "pdf=report.pdf"]).is_err());
+    assert!(cli(&["--repos", "a", "--report", "json", "--report", "json"]).is_err());
+    assert!(find_renderer("JUnit").is_ok());
+}
+
+#[test]
+fn test_every_renderer_writes_its_report() {
+    let config = Config::from_yaml("task_versions:\n  'usenode':\n    - '1'\n").unwrap();
+    let repos = vec!["https://github.com/org/app".to_string()];
+    let mut issues = TaskIssues::default();
+    issues.add_failed_file(
+        &repos[0],
+        std::path::Path::new("ci/broken.yml"),
+        "Parse error: line 3: invalid flow collection",
+    );
+
+    let dir = std::env::temp_dir().join(format!("ciprobe-reports-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for renderer in RENDERERS {
+        let output = ReportOutput::new(*renderer, Some(dir.join(renderer.default_output())));
+        output.write(&repos, &config, &issues).unwrap();
+        let report = std::fs::read_to_string(&output.path).unwrap();
+        assert!(
+            report.contains("ci/broken.yml"),
+            "{} report lists the unparsed file",
+            renderer.name()
+        );
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_markdown_links_to_lines() {
+    let config = Config::from_yaml("task_versions:\n  'usenode':\n    - '1'\n").unwrap();
+    let repo = "https://github.com/org/app".to_string();
+    let mut issues = TaskIssues::default();
+    issues.add_implementation(
+        "UseNode",
+        ciprobe::TaskImplementation {
+            line: 42,
+            column: 7,
+            source: "- task: UseNode@0".to_string(),
+            ..implementation(&repo, "0", "ci/build.yml")
+        },
+        &config,
+        false,
+    );
+
+    let md =
+        ciprobe::report::generate_markdown_report(std::slice::from_ref(&repo), &config, &issues)
+            .unwrap();
+    assert!(md.contains(
+        "- Version 0 in [`ci/build.yml:42`](https://github.com/org/app/blob/HEAD/ci/build.yml#L42)\n  `- task: UseNode@0`\n"
+    ));
+
+    // Links point at the analyzed commit when it is known
+    let commit = "0123456789abcdef0123456789abcdef01234567";
+    issues.repo_commits.insert(repo.clone(), commit.to_string());
+    let md = ciprobe::report::generate_markdown_report(&[repo], &config, &issues).unwrap();
+    assert!(md.contains(&format!(
+        "(https://github.com/org/app/blob/{}/ci/build.yml#L42)",
+        commit
+    )));
+
+    // The header uses the same calendar date as the JSON report
+    let today = &ciprobe::report::utc_timestamp()[..10];
+    assert!(md.contains(&format!("🕒 Generated on: {}T", today)));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use ciprobe::sarif_report::generate_sarif_report;
+use ciprobe::{Config, TaskImplementation, TaskIssues};
+
+fn implementation(repo: &str, file: &str, version: &str) -> TaskImplementation {
+    TaskImplementation {
+        line: 12,
+        column: 11,
+        source: "- task: Example@0".to_string(),
+        ..common::implementation(repo, version, file)
+    }
+}
+
+#[test]
+fn test_sarif_results_per_rule() {
+    let config = Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'docker':\n    policy: forbidden\n",
+    )
+    .unwrap();
+
+    let repo = "https://github.com/org/app";
+    let template_repo = "https://github.com/org/templates#refs/tags/v1";
+    let mut issues = TaskIssues::default();
+    issues.add_implementation(
+        "UseNode",
+        implementation(repo, "azure-pipelines.yml", "0"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "UseNode",
+        implementation(repo, "ci/valid.yml", "1"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "Docker",
+        implementation(template_repo, "build.yml", "2"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "Bash",
+        implementation(repo, "azure-pipelines.yml", "3"),
+        &config,
+        false,
+    );
+
+    let sarif = generate_sarif_report(&[repo.to_string()], &config, &issues).unwrap();
+
+    assert!(sarif.contains("\"version\": \"2.1.0\""));
+    assert_eq!(sarif.matches("\"ruleId\"").count(), 3);
+    assert!(sarif.contains("\"ruleId\": \"ciprobe/invalid-version\""));
+    assert!(sarif.contains("\"ruleId\": \"ciprobe/forbidden-task\""));
+    assert!(sarif.contains("\"ruleId\": \"ciprobe/unknown-task\",\n          \"ruleIndex\": 2,\n          \"level\": \"warning\""));
+    assert!(sarif.contains("Task 'usenode' uses version 0, expected 1"));
+    assert!(!sarif.contains("ci/valid.yml"));
+    assert!(sarif.contains("\"startLine\": 12,\n                  \"startColumn\": 11,\n                  \"snippet\": {\n                    \"text\": \"- task: Example@0\""));
+
+    // One run per repository, with template refs as the branch
+    assert_eq!(sarif.matches("\"repositoryUri\"").count(), 2);
+    assert!(sarif.contains("\"repositoryUri\": \"https://github.com/org/templates\",\n          \"branch\": \"refs/tags/v1\""));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use ciprobe::{Config, TaskIssues, TaskPolicy, YamlConfig};
+use common::implementation;
+use std::path::Path;
+
+#[test]
+fn test_policies_are_parsed() {
+    let config = Config::from_yaml(
+        r#"
+task_versions:
+  'usenode':
+    - '1'
+  'copyfiles':
+    policy: required
+    versions:
+      - '2'
+  'gitversion/setup':
+    policy: required
+  'docker':
+    policy: forbidden
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.policy("usenode"), TaskPolicy::Governed);
+    assert_eq!(config.policy("copyfiles"), TaskPolicy::Required);
+    assert_eq!(config.policy("docker"), TaskPolicy::Forbidden);
+
+    let mut required: Vec<_> = config
+        .get_required_tasks()
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+    required.sort();
+    assert_eq!(required, vec!["copyfiles", "gitversion/setup"]);
+
+    // Required tasks without versions only need to be present
+    assert_eq!(config.get_valid_versions("gitversion/setup"), vec!["*"]);
+}
+
+#[test]
+fn test_unknown_policy_is_rejected() {
+    let result = YamlConfig::parse("task_versions:\n  'Docker':\n    policy: banned\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_governed_task_without_versions_is_rejected() {
+    assert!(Config::from_yaml("task_versions:\n  'usenode': []\n").is_err());
+    assert!(Config::from_yaml("task_versions:\n  'usenode':\n    policy: governed\n").is_err());
+    assert!(Config::from_yaml("task_versions:\n  'docker':\n    policy: forbidden\n").is_ok());
+}
+
+#[test]
+fn test_forbidden_tasks_are_recorded_separately() {
+    let config = Config::from_yaml(
+        "task_versions:\n  'usenode':\n    - '1'\n  'docker':\n    policy: forbidden\n",
+    )
+    .unwrap();
+    let mut issues = TaskIssues::default();
+
+    issues.add_implementation(
+        "Docker",
+        implementation("repo-a", "2", "azure-pipelines.yml"),
+        &config,
+        false,
+    );
+    issues.add_implementation(
+        "UseNode",
+        implementation("repo-a", "0", "azure-pipelines.yml"),
+        &config,
+        false,
+    );
+
+    assert_eq!(issues.forbidden_usages["docker"].len(), 1);
+    assert!(!issues.invalid_states.contains_key("docker"));
+    assert!(!issues.missing_states.contains_key("docker"));
+    assert_eq!(issues.invalid_states["usenode"]["repo-a"].len(), 1);
+}
+
+#[test]
+fn test_list_based_config_still_loads() {
+    let config = Config::load(Some(Path::new(
+        "tests/test_report_generation-ciprobeconfig.yml",
+    )))
+    .unwrap();
+
+    assert_eq!(config.get_all_tasks().len(), 4);
+    assert!(config.get_required_tasks().is_empty());
+    assert_eq!(config.get_valid_versions("usenode"), vec!["1"]);
+}
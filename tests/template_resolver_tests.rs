@@ -0,0 +1,164 @@
+use ciprobe::{analyze_pipelines, AnalysisOptions, Config, RepoEntry, TemplateResolver};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn write_repo(name: &str, files: &[(&str, &str)]) -> (PathBuf, Vec<PathBuf>) {
+    let root = std::env::temp_dir().join(format!("ciprobe-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let mut paths = Vec::new();
+    for (path, content) in files {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(&full, content).unwrap();
+        paths.push(full);
+    }
+    (root, paths)
+}
+
+#[test]
+fn test_resolve_template_path() {
+    let including = Path::new("templates/jobs/build.yml");
+    assert_eq!(
+        TemplateResolver::resolve_template_path(including, "../steps/node.yml"),
+        Some(PathBuf::from("templates/steps/node.yml"))
+    );
+    assert_eq!(
+        TemplateResolver::resolve_template_path(including, "/shared/vars.yml"),
+        Some(PathBuf::from("shared/vars.yml"))
+    );
+    assert_eq!(
+        TemplateResolver::resolve_template_path(including, "build.yml@templates"),
+        None
+    );
+    assert_eq!(
+        TemplateResolver::resolve_template_path(including, "${{ parameters.steps }}"),
+        None
+    );
+}
+
+#[test]
+fn test_tasks_are_attributed_through_templates() {
+    let (root, files) = write_repo(
+        "templates",
+        &[
+            (
+                "azure-pipelines.yml",
+                "variables:\n  - template: vars.yml\njobs:\n  - template: templates/build.yml\n",
+            ),
+            ("vars.yml", "variables:\n  - name: a\n    value: b\n"),
+            (
+                "templates/build.yml",
+                "jobs:\n  - job: Build\n    steps:\n      - template: ../steps/node.yml\n      - task: Docker@2\n",
+            ),
+            (
+                "steps/node.yml",
+                "steps:\n  - task: UseNode@0\n  - template: /templates/build.yml\n",
+            ),
+        ],
+    );
+
+    let resolver = TemplateResolver::load(&root, &files, false).unwrap();
+    assert_eq!(
+        resolver.root_files(),
+        vec![PathBuf::from("azure-pipelines.yml")]
+    );
+
+    let resolved = resolver.resolve().tasks;
+    let use_node = resolved
+        .iter()
+        .find(|r| r.task.name == "UseNode")
+        .expect("UseNode reached through templates");
+    assert_eq!(use_node.file_path, PathBuf::from("steps/node.yml"));
+    assert_eq!(
+        use_node.include_chain,
+        vec![
+            PathBuf::from("azure-pipelines.yml"),
+            PathBuf::from("templates/build.yml"),
+            PathBuf::from("steps/node.yml"),
+        ]
+    );
+
+    // The recursive include back into build.yml is not followed again.
+    assert_eq!(
+        resolved.iter().filter(|r| r.task.name == "Docker").count(),
+        1
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_external_templates_use_root_resources() {
+    let (root, files) = write_repo(
+        "external",
+        &[
+            (
+                "azure-pipelines.yml",
+                "resources:\n  repositories:\n    - repository: templates\n      type: git\n      name: Shared/templates\nsteps:\n  - template: steps/local.yml\n",
+            ),
+            (
+                "steps/local.yml",
+                "steps:\n  - template: /build.yml@templates\n  - template: other.yml@unknown\n",
+            ),
+        ],
+    );
+
+    let resolver = TemplateResolver::load(&root, &files, false).unwrap();
+    let external = resolver.resolve().external_templates;
+    assert_eq!(external.len(), 1);
+    assert_eq!(external[0].repository.name, "Shared/templates");
+    assert_eq!(external[0].path, PathBuf::from("build.yml"));
+    assert_eq!(
+        external[0].include_chain,
+        vec![
+            PathBuf::from("azure-pipelines.yml"),
+            PathBuf::from("steps/local.yml"),
+        ]
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_resolve_from_template_entry() {
+    let (root, files) = write_repo(
+        "shared",
+        &[
+            ("build.yml", "steps:\n  - template: steps/node.yml\n"),
+            ("steps/node.yml", "steps:\n  - task: UseNode@0\n"),
+        ],
+    );
+
+    let resolver = TemplateResolver::load(&root, &files, false).unwrap();
+    let resolved = resolver.resolve_from(Path::new("build.yml"));
+    assert_eq!(resolved.len(), 1);
+    assert_eq!(resolved[0].file_path, PathBuf::from("steps/node.yml"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_template_shared_by_roots_is_reported_once() {
+    let (root, _) = write_repo(
+        "shared",
+        &[
+            ("build.yml", "steps:\n  - template: steps/node.yml\n"),
+            ("release.yml", "steps:\n  - template: steps/node.yml\n"),
+            ("steps/node.yml", "steps:\n  - task: UseNode@0\n"),
+        ],
+    );
+
+    let config = Config::from_yaml("task_versions:\n  'usenode':\n    - '1'\n").unwrap();
+    let issues = analyze_pipelines(
+        &[RepoEntry::local(&root).unwrap()],
+        None,
+        &config,
+        &AnalysisOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(issues.all_implementations["usenode"].len(), 1);
+    assert_eq!(issues.invalid_states["usenode"].len(), 1);
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -1,4 +1,4 @@
-use ciprobe::VersionCompare;
+use ciprobe::{VersionCompare, VersionRequirement};
 
 #[test]
 fn test_version_comparison() {
@@ -44,3 +44,70 @@ fn test_invalid_version_handling() {
     assert!(!"invalid".to_string().version_matches("1.0")); // Invalid vs valid
     assert!(!"1.a.0".to_string().version_matches("1.0.0")); // Partially invalid
 }
+
+#[test]
+fn test_prefixed_and_ref_versions() {
+    assert!("v4".to_string().version_matches("4"));
+    assert!("4".to_string().version_matches("v4.0"));
+    assert!(!"v4".to_string().version_matches("v5"));
+    assert!("main".to_string().version_matches("main"));
+    assert!(!"main".to_string().version_matches("master"));
+}
+
+#[test]
+fn test_version_ordering() {
+    use std::cmp::Ordering;
+
+    assert_eq!("2".version_cmp("2.0.0"), Some(Ordering::Equal));
+    assert_eq!("2.1".version_cmp("2.0.9"), Some(Ordering::Greater));
+    assert_eq!("v3".version_cmp("10"), Some(Ordering::Less));
+    assert_eq!("main".version_cmp("1"), None);
+}
+
+#[test]
+fn test_version_requirements() {
+    let test_cases = vec![
+        // Plain versions keep exact matching
+        ("2", "2.0.0", true),
+        ("2", "2.1", false),
+        ("main", "main", true),
+        // Ranges
+        (">=3, <5", "3", true),
+        (">=3, <5", "4.9.1", true),
+        (">=3, <5", "5", false),
+        (">=3, <5", "2", false),
+        (">2", "2.0.1", true),
+        ("<=1.4", "1.4.0", true),
+        // Caret and tilde
+        ("^2", "2.7", true),
+        ("^2", "3", false),
+        ("^0.2.3", "0.2.9", true),
+        ("^0.2.3", "0.3.0", false),
+        ("~1.4", "1.4.7", true),
+        ("~1.4", "1.5", false),
+        ("~1", "1.9", true),
+        // Wildcard matches anything, ranges never match branch names
+        ("*", "main", true),
+        (">=1", "main", false),
+    ];
+
+    for (requirement, version, expected) in test_cases {
+        assert_eq!(
+            VersionRequirement::parse(requirement)
+                .unwrap()
+                .matches(version),
+            expected,
+            "Testing {} against {} (expected: {})",
+            version,
+            requirement,
+            expected
+        );
+    }
+
+    assert!(VersionRequirement::parse(">=abc").is_err());
+    assert!(VersionRequirement::parse("^").is_err());
+    // The upper bound of a range must not overflow
+    assert!(VersionRequirement::parse("^4294967295").is_err());
+    assert!(VersionRequirement::parse("~1.4294967295").is_err());
+    assert!(VersionRequirement::parse("^4294967294").is_ok());
+}